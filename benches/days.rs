@@ -0,0 +1,91 @@
+//! Timings for the parse and solve phases of every day, plus a few of the
+//! hot spots inside them. Run with `cargo bench`, optionally passing a
+//! filter to only run benchmarks whose names contain it, e.g.
+//! `cargo bench -- day5`.
+
+use std::env;
+use aoc2021::bench::{black_box, measure, Pretty, Stats};
+use aoc2021::inputs::load;
+use aoc2021::{day1, day2, day3, day4, day5, day6, day7, day8, Solution};
+
+const ITERATIONS: usize = 20;
+
+struct Runner {
+    filter: Option<String>,
+}
+
+impl Runner {
+    fn bench<T>(&self, name: &str, work: impl FnMut() -> T) {
+        if let Some(filter) = &self.filter {
+            if !name.contains(filter.as_str()) {
+                return;
+            }
+        }
+
+        let Stats { min, median, max, .. } = measure(ITERATIONS, work);
+        println!("{:<32} {:>10} {:>10} {:>10}", name, Pretty(min), Pretty(median), Pretty(max));
+    }
+
+    /// Benchmark the parse phase and both parts of a day's solution
+    fn day<S: Solution>(&self, day: u32) {
+        let input = load(day).unwrap();
+        let parsed = S::parse(&input).unwrap();
+
+        self.bench(&format!("day{}/parse", day), || S::parse(black_box(&input)).unwrap());
+        self.bench(&format!("day{}/part1", day), || S::part1(black_box(&parsed)).unwrap());
+        self.bench(&format!("day{}/part2", day), || S::part2(black_box(&parsed)).unwrap());
+    }
+}
+
+fn main() {
+    // `cargo bench` passes `--bench` along, which isn't a filter
+    let filter = env::args().skip(1).find(|x| !x.starts_with("--"));
+    let runner = Runner { filter };
+
+    println!("{:<32} {:>10} {:>10} {:>10}", "benchmark", "min", "median", "max");
+
+    runner.day::<day1::Day1>(1);
+    runner.day::<day2::Day2>(2);
+    runner.day::<day3::Day3>(3);
+    runner.day::<day4::Day4>(4);
+    runner.day::<day5::Day5>(5);
+    runner.day::<day6::Day6>(6);
+    runner.day::<day7::Day7>(7);
+    runner.day::<day8::Day8>(8);
+
+    let lines = day5::Day5::parse(&load(5).unwrap()).unwrap();
+    runner.bench("day5/add_vent", || {
+        let mut floor = day5::OceanFloor::default();
+        for line in &lines {
+            floor.add_vent(line.clone()).unwrap();
+        }
+        floor
+    });
+
+    let displays = load(8).unwrap();
+    runner.bench("day8/parse_input", || {
+        displays
+            .lines()
+            .map(|x| day8::Display::parse_input(black_box(x)).unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    let diagnostics = load(3).unwrap();
+    let samples: Vec<&str> = diagnostics.lines().collect();
+    runner.bench("day3/rate_life_support", || {
+        let mut samples = black_box(samples.clone());
+        day3::rate_life_support(&mut samples).unwrap()
+    });
+
+    let packed = day3::PackedDiagnostics::new(&samples).unwrap();
+    runner.bench("day3/packed_life_support", || black_box(&packed).rate_life_support().unwrap());
+
+    let population = day6::Day6::parse(&load(6).unwrap()).unwrap();
+    runner.bench("day6/be_fruitful_256", || {
+        let mut population = black_box(population);
+        for _ in 0..256 {
+            day6::be_fruitful(&mut population);
+        }
+        population
+    });
+}
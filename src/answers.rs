@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+use crate::inputs::{Inputs, Kind};
+use crate::report::Record;
+use crate::{Error, Part, Result};
+
+/// Name of the answers manifest, which lives alongside the inputs it has
+/// answers for
+pub const ANSWERS_FILE: &str = "answers.toml";
+
+/// Name of the manifest holding the answers to the worked examples
+pub const EXAMPLES_FILE: &str = "examples.toml";
+
+/// Known-good answers, keyed by day and part
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Answers {
+    known: BTreeMap<(u32, Part), u64>,
+}
+
+impl Answers {
+    /// The expected answer for one part of a day's puzzle, if known
+    pub fn expected(&self, day: u32, part: Part) -> Option<u64> {
+        self.known.get(&(day, part)).copied()
+    }
+
+    pub fn insert(&mut self, day: u32, part: Part, answer: u64) {
+        self.known.insert((day, part), answer);
+    }
+
+    /// Read the manifest for the given kind of input from an input directory
+    pub fn load(inputs: &Inputs, kind: Kind) -> Result<Self> {
+        let path = inputs.dir().join(match kind {
+            Kind::Puzzle => ANSWERS_FILE,
+            Kind::Example => EXAMPLES_FILE,
+        });
+
+        let text = fs::read_to_string(&path).map_err(|source| Error::Io { path: Some(path), source })?;
+        text.parse()
+    }
+
+    /// Compare what actually happened against the expected answer
+    pub fn check(&self, record: &Record) -> Verdict {
+        match (&record.outcome, self.expected(record.day, record.part)) {
+            (Err(e), _) => Verdict::Failed(e.clone()),
+            (Ok(_), None) => Verdict::Unknown,
+            (Ok(run), Some(expected)) if run.answer == expected => Verdict::Pass,
+            (Ok(run), Some(expected)) => Verdict::Mismatch { expected, actual: run.answer },
+        }
+    }
+}
+
+impl FromStr for Answers {
+    type Err = Error;
+
+    /// Parses a manifest made of a `[dayN]` table for each day, holding
+    /// `part1` and/or `part2` keys. This is a small subset of TOML.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aoc2021::Part;
+    /// use aoc2021::answers::Answers;
+    ///
+    /// let answers: Answers = "# Sonar sweep\n[day1]\npart1 = 7\npart2 = 5\n\n[day6]\npart2 = 26984457539".parse()?;
+    /// assert_eq!(answers.expected(1, Part::One), Some(7));
+    /// assert_eq!(answers.expected(6, Part::Two), Some(26984457539));
+    /// assert_eq!(answers.expected(6, Part::One), None);
+    ///
+    /// assert!("part1 = 7".parse::<Answers>().is_err());
+    /// assert!("[day1]\npart3 = 7".parse::<Answers>().is_err());
+    /// assert!("[day1]\npart1 = seven".parse::<Answers>().is_err());
+    /// assert!("[dayone]\npart1 = 7".parse::<Answers>().is_err());
+    /// # Ok::<(), aoc2021::Error>(())
+    /// ```
+    fn from_str(s: &str) -> Result<Self> {
+        let mut answers = Self::default();
+        let mut day = None;
+
+        for (n, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let at = |message: String| Error::parse(message).at_line(n + 1);
+
+            if let Some(table) = line.strip_prefix('[').and_then(|x| x.strip_suffix(']')) {
+                let number = table
+                    .trim()
+                    .strip_prefix("day")
+                    .and_then(|x| x.parse().ok())
+                    .ok_or_else(|| at(format!("Expected a table named like [day1], not [{}]", table)))?;
+
+                day = Some(number);
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| at(format!("Expected `key = value`, not {}", line)))?;
+
+            let day = day.ok_or_else(|| at("Answers have to go inside a [dayN] table".into()))?;
+            let part = match key.trim() {
+                "part1" => Part::One,
+                "part2" => Part::Two,
+                unknown => return Err(at(format!("Unknown key `{}`, expected part1 or part2", unknown))),
+            };
+
+            let answer = value
+                .trim()
+                .parse()
+                .map_err(|_| at(format!("Answers are positive integers, not {}", value.trim())))?;
+
+            answers.insert(day, part, answer);
+        }
+
+        Ok(answers)
+    }
+}
+
+/// How a day's answer compares to the known-good one
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// The answer matches
+    Pass,
+    /// The answer doesn't match
+    Mismatch { expected: u64, actual: u64 },
+    /// There was an answer, but nothing to compare it to
+    Unknown,
+    /// No answer could be found at all
+    Failed(String),
+}
+
+impl Verdict {
+    /// Whether this verdict means something is wrong
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Self::Mismatch { .. } | Self::Failed(_))
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Pass => write!(f, "pass"),
+            Self::Mismatch { expected, actual } => write!(f, "mismatch (expected {}, got {})", expected, actual),
+            Self::Unknown => write!(f, "unknown (no answer on record)"),
+            Self::Failed(why) => write!(f, "fail ({})", why),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::inputs::DEFAULT_INPUT_DIR;
+    use crate::report::run_all;
+    use crate::Run;
+
+    #[test]
+    fn verdicts() {
+        let mut answers = Answers::default();
+        answers.insert(1, Part::One, 7);
+
+        let record = |day, answer| Record {
+            day,
+            part: Part::One,
+            outcome: Ok(Run { answer, parse_time: Duration::ZERO, solve_time: Duration::ZERO }),
+        };
+
+        assert_eq!(answers.check(&record(1, 7)), Verdict::Pass);
+        assert_eq!(answers.check(&record(1, 8)), Verdict::Mismatch { expected: 7, actual: 8 });
+        assert_eq!(answers.check(&record(2, 8)), Verdict::Unknown);
+
+        let failed = Record { day: 1, part: Part::One, outcome: Err("oops".into()) };
+        assert_eq!(answers.check(&failed), Verdict::Failed("oops".into()));
+    }
+
+    #[test]
+    fn bundled_answers() {
+        let inputs = Inputs::new(DEFAULT_INPUT_DIR);
+        let answers = Answers::load(&inputs, Kind::Puzzle).unwrap();
+
+        for record in run_all(crate::solvers(), &inputs, Kind::Puzzle) {
+            assert_eq!(answers.check(&record), Verdict::Pass, "day {} part {}", record.day, record.part);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+use crate::{Part, Result, Solver};
+
+/// Hide a value from the optimizer, so that work whose result is never
+/// looked at doesn't get compiled away
+pub fn black_box<T>(value: T) -> T {
+    // Safety: `value` is read exactly once and then forgotten, so this is
+    // just a move the compiler can't see through
+    unsafe {
+        let result = std::ptr::read_volatile(&value);
+        std::mem::forget(value);
+        result
+    }
+}
+
+/// Summary of repeatedly timing the same piece of work
+#[derive(Clone, Copy, Debug)]
+pub struct Stats {
+    pub iterations: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    /// Summarize a set of timings. There must be at least one of them.
+    pub fn from_samples(mut samples: Vec<Duration>) -> Self {
+        samples.sort_unstable();
+        Self {
+            iterations: samples.len(),
+            min: samples[0],
+            median: samples[samples.len() / 2],
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// Time a piece of work over the given number of iterations (at least one)
+pub fn measure<T>(iterations: usize, mut work: impl FnMut() -> T) -> Stats {
+    let samples = (0..iterations.max(1))
+        .map(|_| {
+            let start = Instant::now();
+            black_box(work());
+            start.elapsed()
+        })
+        .collect();
+
+    Stats::from_samples(samples)
+}
+
+/// Timings for both phases of solving one part of a day's puzzle
+#[derive(Clone, Copy, Debug)]
+pub struct Timing {
+    pub day: u32,
+    pub part: Part,
+    pub answer: u64,
+    pub parse: Stats,
+    pub solve: Stats,
+}
+
+/// Repeatedly run a solver against an input, timing the parse and solve
+/// phases separately
+pub fn time_solver(solver: &dyn Solver, input: &str, part: Part, iterations: usize) -> Result<Timing> {
+    let mut parse = vec![];
+    let mut solve = vec![];
+    let mut answer = 0;
+
+    for _ in 0..iterations.max(1) {
+        let run = solver.run(input, part)?;
+        parse.push(run.parse_time);
+        solve.push(run.solve_time);
+        answer = run.answer;
+    }
+
+    Ok(Timing {
+        day: solver.day(),
+        part,
+        answer,
+        parse: Stats::from_samples(parse),
+        solve: Stats::from_samples(solve),
+    })
+}
+
+/// Displays a duration with a unit that keeps it readable, e.g. `12.3µs`
+pub struct Pretty(pub Duration);
+
+impl fmt::Display for Pretty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let nanos = self.0.as_nanos() as f64;
+        let text = if nanos < 1e3 {
+            format!("{}ns", nanos)
+        } else if nanos < 1e6 {
+            format!("{:.1}µs", nanos / 1e3)
+        } else if nanos < 1e9 {
+            format!("{:.1}ms", nanos / 1e6)
+        } else {
+            format!("{:.2}s", nanos / 1e9)
+        };
+
+        f.pad(&text)
+    }
+}
+
+/// Render timings as a table, one row per day and part
+pub fn table(timings: &[Timing]) -> String {
+    let mut out = format!(
+        "{:>3} {:>4} {:>16} {:>10} {:>10} {:>10}\n",
+        "day", "part", "answer", "parse", "solve", "total");
+
+    for t in timings {
+        out += &format!(
+            "{:>3} {:>4} {:>16} {:>10} {:>10} {:>10}\n",
+            t.day,
+            t.part,
+            t.answer,
+            Pretty(t.parse.median),
+            Pretty(t.solve.median),
+            Pretty(t.parse.median + t.solve.median));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats() {
+        let ms = Duration::from_millis;
+        let stats = Stats::from_samples(vec![ms(5), ms(1), ms(3)]);
+        assert_eq!(stats.iterations, 3);
+        assert_eq!(stats.min, ms(1));
+        assert_eq!(stats.median, ms(3));
+        assert_eq!(stats.max, ms(5));
+    }
+
+    #[test]
+    fn pretty() {
+        assert_eq!(Pretty(Duration::from_nanos(999)).to_string(), "999ns");
+        assert_eq!(Pretty(Duration::from_nanos(12_345)).to_string(), "12.3µs");
+        assert_eq!(Pretty(Duration::from_micros(4_560)).to_string(), "4.6ms");
+        assert_eq!(Pretty(Duration::from_millis(2_500)).to_string(), "2.50s");
+        assert_eq!(format!("{:>6}", Pretty(Duration::from_nanos(7))), "   7ns");
+    }
+
+    #[test]
+    fn solver_timings() {
+        let day1 = crate::solver(1).unwrap();
+        let timing = time_solver(day1, "1\n2\n3\n4", Part::Two, 3).unwrap();
+
+        assert_eq!(timing.day, 1);
+        assert_eq!(timing.answer, 1);
+        assert_eq!(timing.parse.iterations, 3);
+        assert!(table(&[timing]).lines().nth(1).unwrap().contains("  1    2"));
+    }
+}
@@ -0,0 +1,178 @@
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use eyre::{Result, WrapErr};
+use aoc2021::inputs::{self, Inputs, Kind};
+use aoc2021::answers::Answers;
+use aoc2021::{bench, report, Part, Solver};
+
+const USAGE: &str = "usage: aoc2021 <day> <part> [input-file]
+       aoc2021 all [--json] [day...]
+       aoc2021 verify [--examples] [day...]
+       aoc2021 bench [--iterations <n>] [day...]
+
+Solves one part of a day's puzzle and prints the answer. The input is
+read from the given file, or from stdin if the file is `-`. Without a
+file, the day's input is looked up in $AOC2021_INPUT_DIR, then in the
+input_dir named by ./aoc2021.conf, then in the inputs bundled with the
+crate.
+
+The all mode solves both parts of the given days (or every day) against
+their configured inputs, carrying on past any failures. With --json it
+prints one JSON object per line, holding the day, part, answer, parse
+and solve times in nanoseconds, and any error.
+
+The verify mode solves both parts of the given days (or every day) and
+checks the answers against the answers.toml kept in the input directory,
+reporting whether each passed, failed, or didn't match. It exits with a
+failure status if anything failed or didn't match. With --examples, the
+worked examples (dayN.example) are checked against examples.toml instead.
+
+The bench mode times the parse and solve phases of both parts of the
+given days (or every day) against their configured inputs, and prints
+the median of each over the given number of iterations (default 10).";
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    std::process::exit(2);
+}
+
+fn read_input(day: u32, path: Option<&str>) -> Result<String> {
+    match path {
+        None => Ok(inputs::load(day)?),
+        Some("-") => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input).wrap_err("Couldn't read input from stdin")?;
+            Ok(input)
+        }
+        Some(path) => fs::read_to_string(path).wrap_err_with(|| format!("Couldn't read input from {}", path)),
+    }
+}
+
+fn parse_day(arg: &str) -> Result<u32> {
+    arg.parse().wrap_err_with(|| format!("Not a day of the month: {}", arg))
+}
+
+fn find_solver(day: u32) -> Result<&'static dyn Solver> {
+    aoc2021::solver(day).ok_or_else(|| eyre::eyre!("Day {} hasn't been solved yet", day))
+}
+
+/// The solvers for the given days, or all of them if none were given
+fn select_solvers(days: Vec<u32>) -> Result<Vec<&'static dyn Solver>> {
+    if days.is_empty() {
+        Ok(aoc2021::solvers().to_vec())
+    } else {
+        days.into_iter().map(find_solver).collect()
+    }
+}
+
+fn solve(args: &[String]) -> Result<()> {
+    if args.len() < 2 || args.len() > 3 {
+        usage();
+    }
+
+    let day = parse_day(&args[0])?;
+    let part: Part = args[1].parse()?;
+    let solver = find_solver(day)?;
+
+    let input = read_input(day, args.get(2).map(String::as_str))?;
+    println!("{}", solver.solve(&input, part)?);
+
+    Ok(())
+}
+
+fn benchmark(args: &[String]) -> Result<()> {
+    let mut iterations = 10;
+    let mut days = vec![];
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--iterations" => {
+                let n = args.next().unwrap_or_else(|| usage());
+                iterations = n.parse().wrap_err_with(|| format!("Not a number of iterations: {}", n))?;
+            }
+            day => days.push(parse_day(day)?),
+        }
+    }
+
+    let mut timings = vec![];
+    for solver in select_solvers(days)? {
+        let input = inputs::load(solver.day())?;
+        for part in [Part::One, Part::Two] {
+            timings.push(bench::time_solver(solver, &input, part, iterations)?);
+        }
+    }
+
+    print!("{}", bench::table(&timings));
+    Ok(())
+}
+
+fn run_all(args: &[String]) -> Result<()> {
+    let mut json = false;
+    let mut days = vec![];
+
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            day => days.push(parse_day(day)?),
+        }
+    }
+
+    let records = report::run_all(&select_solvers(days)?, &Inputs::from_env()?, Kind::Puzzle);
+    for record in &records {
+        match (json, &record.outcome) {
+            (true, _) => println!("{}", record.to_json()),
+            (false, Ok(run)) => println!("{:>3} {:>4} {:>16}", record.day, record.part, run.answer),
+            (false, Err(e)) => println!("{:>3} {:>4} {:>16} ({})", record.day, record.part, "error", e),
+        }
+    }
+
+    Ok(())
+}
+
+fn verify(args: &[String]) -> Result<()> {
+    let mut kind = Kind::Puzzle;
+    let mut days = vec![];
+
+    for arg in args {
+        match arg.as_str() {
+            "--examples" => kind = Kind::Example,
+            day => days.push(parse_day(day)?),
+        }
+    }
+
+    let inputs = Inputs::from_env()?;
+    let answers = Answers::load(&inputs, kind)?;
+    let records = report::run_all(&select_solvers(days)?, &inputs, kind);
+
+    let mut failures = 0;
+    for record in &records {
+        let verdict = answers.check(record);
+        if verdict.is_failure() {
+            failures += 1;
+        }
+
+        println!("{:>3} {:>4} {}", record.day, record.part, verdict);
+    }
+
+    if failures > 0 {
+        eyre::bail!("{} of {} answers failed verification", failures, records.len());
+    }
+
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|x| x == "-h" || x == "--help") {
+        usage();
+    }
+
+    match args[0].as_str() {
+        "all" => run_all(&args[1..]),
+        "verify" => verify(&args[1..]),
+        "bench" => benchmark(&args[1..]),
+        _ => solve(&args),
+    }
+}
@@ -1,5 +1,12 @@
 use std::cmp::PartialOrd;
+use std::io::BufRead;
 use std::ops::Add;
+use crate::{Error, Result, Solution};
+
+pub mod anomaly;
+pub mod sweep;
+pub mod trend;
+pub mod window;
 
 /// Given a sequence of measurements, count the number of times
 /// that the measurement increases from the previous measurement.
@@ -38,19 +45,194 @@ pub fn count_triples<T>(measurements: &[T]) -> usize where T: PartialOrd + Copy
     count_measurements(&triplet_sums)
 }
 
+/// Given a stream of measurements, count the number of times that
+/// the measurement increases from the previous measurement. Only the
+/// previous measurement is held on to, so the stream can be as long
+/// as it likes.
+///
+/// # Examples
+/// ```
+/// use aoc2021::day1::count_measurements_iter;
+/// let sample = "199 200 208 210 200 207 240 269 260 263";
+/// let measurements = sample.split(' ').map(|x| x.parse::<u32>().unwrap());
+/// assert_eq!(count_measurements_iter(measurements), 7);
+/// ```
+pub fn count_measurements_iter<I>(measurements: I) -> usize where I: IntoIterator, I::Item: PartialOrd {
+    let mut measurements = measurements.into_iter();
+    let mut previous = match measurements.next() {
+        Some(x) => x,
+        None => return 0,
+    };
+
+    let mut count = 0;
+    for x in measurements {
+        if x > previous {
+            count += 1;
+        }
+        previous = x;
+    }
+
+    count
+}
+
+/// Given a stream of measurements, count the number of times that the
+/// sum of subsequent sliding three-element windows is strictly increasing,
+/// without collecting the window sums.
+///
+/// # Examples
+/// ```
+/// use aoc2021::day1::count_triples_iter;
+/// let sample = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+/// assert_eq!(count_triples_iter(sample.iter().copied()), 5);
+/// ```
+pub fn count_triples_iter<I>(measurements: I) -> usize
+where I: IntoIterator, I::Item: PartialOrd + Copy + Add<Output=I::Item> {
+    let mut counter = SweepCounter::new();
+    measurements.into_iter().for_each(|x| counter.push(x));
+    counter.triple_increases()
+}
+
+/// Keeps a running count of both kinds of increase as measurements
+/// arrive one at a time, holding on to no more than the last three
+/// of them. Handy when the measurements come from somewhere that can
+/// fail partway through.
+///
+/// # Examples
+/// ```
+/// use std::io::Cursor;
+/// use aoc2021::day1::{read_measurements, SweepCounter};
+///
+/// let reader = Cursor::new("199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n");
+/// let mut counter = SweepCounter::new();
+/// for measurement in read_measurements(reader) {
+///     counter.push(measurement?);
+/// }
+///
+/// assert_eq!(counter.increases(), 7);
+/// assert_eq!(counter.triple_increases(), 5);
+/// # Ok::<(), aoc2021::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct SweepCounter<T> {
+    recent: [Option<T>; 3],
+    increases: usize,
+    triple_increases: usize,
+}
+
+impl<T> Default for SweepCounter<T> {
+    fn default() -> Self {
+        Self { recent: [None, None, None], increases: 0, triple_increases: 0 }
+    }
+}
+
+impl<T> SweepCounter<T> where T: PartialOrd + Copy + Add<Output=T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, measurement: T) {
+        if let Some(previous) = self.recent[2] {
+            if measurement > previous {
+                self.increases += 1;
+            }
+        }
+
+        if let [Some(oldest), Some(a), Some(b)] = self.recent {
+            if a + b + measurement > oldest + a + b {
+                self.triple_increases += 1;
+            }
+        }
+
+        self.recent = [self.recent[1], self.recent[2], Some(measurement)];
+    }
+
+    /// How many measurements so far were larger than the one before
+    pub fn increases(&self) -> usize {
+        self.increases
+    }
+
+    /// How many three-measurement windows so far summed to more than the
+    /// window before
+    pub fn triple_increases(&self) -> usize {
+        self.triple_increases
+    }
+}
+
+/// Read a single sweep of measurements from a reader a line at a time.
+/// Lines follow the same rules as in `sweep::parse_sweeps`: anything after
+/// a `#` is a comment, and lines with nothing else on them are skipped.
+/// A blank line between two measurements would start another sweep, so
+/// the measurement after it is an error rather than being counted as part
+/// of this one. Each bad line is reported with its line number, and
+/// reading can carry on past it.
+pub fn read_measurements<R: BufRead>(reader: R) -> impl Iterator<Item = Result<u32>> {
+    let mut started = false;
+    let mut blank = None;
+
+    reader
+        .lines()
+        .enumerate()
+        .filter_map(move |(n, line)| {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e.into())),
+            };
+
+            if line.trim().is_empty() {
+                if started {
+                    blank = blank.or(Some(n + 1));
+                }
+                return None;
+            }
+
+            let measurement = sweep::parse_line(&line).map_err(|e| e.at_line(n + 1)).transpose()?;
+            started = true;
+
+            match (&measurement, blank) {
+                (Ok(_), Some(at)) => {
+                    blank = None;
+                    Some(Err(Error::parse(format!("Expected a single sweep, but line {} is blank", at)).at_line(n + 1)))
+                }
+                _ => Some(measurement),
+            }
+        })
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Input = Vec<Vec<u32>>;
+
+    /// Each sweep in the input is counted separately, and the counts
+    /// added up
+    fn parse(input: &str) -> Result<Self::Input> {
+        sweep::parse_sweeps(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<u64> {
+        Ok(input.iter().map(|x| count_measurements(x) as u64).sum())
+    }
+
+    fn part2(input: &Self::Input) -> Result<u64> {
+        Ok(input.iter().map(|x| count_triples(x) as u64).sum())
+    }
+}
+
 #[cfg(test)]
 mod answers {
     use super::*;
+    use std::fs::File;
+    use std::io::BufReader;
+    use crate::inputs::{Inputs, Kind, DEFAULT_INPUT_DIR};
     use once_cell::sync::OnceCell;
 
     static INPUT: OnceCell<Vec<u32>> = OnceCell::new();
 
     fn get_input() -> &'static Vec<u32> {
         INPUT.get_or_init(|| {
-            include_str!("./input/day1")
-                .split_ascii_whitespace()
-                .filter_map(|line| line.parse().ok())
-                .collect()
+            let sweeps = sweep::parse_sweeps(&Inputs::new(DEFAULT_INPUT_DIR).load(1, Kind::Puzzle).unwrap()).unwrap();
+            assert_eq!(sweeps.len(), 1);
+            sweeps.into_iter().next().unwrap()
         })
     }
 
@@ -63,4 +245,38 @@ mod answers {
     fn puzzle2() {
         assert_eq!(count_triples(get_input()), 1728);
     }
+
+    #[test]
+    fn streaming() {
+        let path = Inputs::new(DEFAULT_INPUT_DIR).path(1, Kind::Puzzle).unwrap();
+        let reader = BufReader::new(File::open(path).unwrap());
+
+        let mut counter = SweepCounter::new();
+        for measurement in read_measurements(reader) {
+            counter.push(measurement.unwrap());
+        }
+
+        assert_eq!(counter.increases(), 1688);
+        assert_eq!(counter.triple_increases(), 1728);
+        assert_eq!(count_measurements_iter(get_input().iter()), 1688);
+    }
+
+    #[test]
+    fn bad_lines() {
+        let results: Vec<_> = read_measurements("\n1\n# comment\n2 # two\nthree\n4".as_bytes()).collect();
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[1].as_ref().unwrap(), &2);
+        assert_eq!(results[2].as_ref().unwrap_err().to_string(), "line 5, column 1: Bad measurement: three");
+        assert_eq!(results[3].as_ref().unwrap(), &4);
+    }
+
+    #[test]
+    fn single_sweep() {
+        let results: Vec<_> = read_measurements("1\n2\n\n  \n3\n4\n\n".as_bytes()).collect();
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[2].as_ref().unwrap_err().to_string(), "line 5: Expected a single sweep, but line 3 is blank");
+        assert_eq!(results[3].as_ref().unwrap(), &4);
+    }
 }
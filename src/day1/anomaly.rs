@@ -0,0 +1,178 @@
+/// A way of deciding that a measurement is a glitch rather than a real
+/// change in depth
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Threshold {
+    /// Flags measurements that differ by more than this from both
+    /// neighbors, so a lone spike is caught but a lasting change in depth
+    /// isn't. The first and last measurements have only one neighbor, so
+    /// there's no telling a spike there from a change, and they're never
+    /// flagged.
+    Jump(f64),
+    /// Flags measurements more than `limit` standard deviations from the
+    /// mean of the `window` measurements before them
+    ZScore { window: usize, limit: f64 },
+    /// Flags measurements more than `limit` scaled median absolute
+    /// deviations from the median of the `window` measurements before
+    /// them. Less swayed by earlier glitches than `ZScore`.
+    Mad { window: usize, limit: f64 },
+}
+
+/// Scales the median absolute deviation so it estimates the standard
+/// deviation of normally distributed measurements
+const MAD_SCALE: f64 = 1.4826;
+
+/// Picks out the measurements in a sweep that look like glitches, by
+/// any of a number of thresholds.
+///
+/// The rolling thresholds only look at the measurements before the one
+/// being judged, so the first `window` measurements are never flagged by
+/// them. Nor is anything flagged when the window is perfectly flat, since
+/// there's no spread to judge against.
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day1::anomaly::{AnomalyDetector, Threshold};
+/// use aoc2021::day1::count_measurements;
+/// let glitchy = [199, 200, 208, 210, 2000, 207, 240, 269, 260, 263];
+/// let detector = AnomalyDetector::default().with(Threshold::Jump(500.0));
+///
+/// assert_eq!(detector.detect(&glitchy), vec![4]);
+/// assert_eq!(count_measurements(&glitchy), 7);
+/// assert_eq!(count_measurements(&detector.clean(&glitchy)), 6);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AnomalyDetector {
+    thresholds: Vec<Threshold>,
+}
+
+impl AnomalyDetector {
+    /// Also flag measurements that cross the given threshold. Panics if a
+    /// rolling threshold has fewer than two measurements in its window,
+    /// since they'd have no spread.
+    pub fn with(mut self, threshold: Threshold) -> Self {
+        if let Threshold::ZScore { window, .. } | Threshold::Mad { window, .. } = threshold {
+            assert!(window >= 2, "Rolling thresholds need at least two measurements in their window");
+        }
+
+        self.thresholds.push(threshold);
+        self
+    }
+
+    pub fn thresholds(&self) -> &[Threshold] {
+        &self.thresholds
+    }
+
+    /// The indices of every measurement flagged by any of the thresholds,
+    /// in order
+    pub fn detect<T>(&self, measurements: &[T]) -> Vec<usize> where T: Copy + Into<f64> {
+        let values: Vec<f64> = measurements.iter().map(|&x| x.into()).collect();
+
+        (0..values.len())
+            .filter(|&idx| self.thresholds.iter().any(|&threshold| is_anomaly(&values, idx, threshold)))
+            .collect()
+    }
+
+    /// The measurements with everything flagged by `detect` left out
+    pub fn clean<T>(&self, measurements: &[T]) -> Vec<T> where T: Copy + Into<f64> {
+        let flagged = self.detect(measurements);
+
+        measurements
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| flagged.binary_search(idx).is_err())
+            .map(|(_, &x)| x)
+            .collect()
+    }
+}
+
+fn is_anomaly(values: &[f64], idx: usize, threshold: Threshold) -> bool {
+    let x = values[idx];
+
+    match threshold {
+        Threshold::Jump(limit) => match (idx.checked_sub(1).and_then(|i| values.get(i)), values.get(idx + 1)) {
+            (Some(before), Some(after)) => (x - before).abs() > limit && (x - after).abs() > limit,
+            _ => false,
+        },
+        Threshold::ZScore { window, limit } => {
+            if idx < window {
+                return false;
+            }
+
+            let (mean, deviation) = mean_and_deviation(&values[idx - window..idx]);
+            deviation > 0.0 && (x - mean).abs() / deviation > limit
+        }
+        Threshold::Mad { window, limit } => {
+            if idx < window {
+                return false;
+            }
+
+            let window = &values[idx - window..idx];
+            let center = median(window.to_vec());
+            let mad = median(window.iter().map(|y| (y - center).abs()).collect());
+            mad > 0.0 && (x - center).abs() / (MAD_SCALE * mad) > limit
+        }
+    }
+}
+
+/// The mean and population standard deviation
+fn mean_and_deviation(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+
+    (mean, variance.sqrt())
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thresholds() {
+        let series = [10, 11, 10, 11, 10, 11, 100, 11, 12, 11];
+
+        let jump = AnomalyDetector::default().with(Threshold::Jump(20.0));
+        assert_eq!(jump.detect(&series), vec![6]);
+
+        let zscore = AnomalyDetector::default().with(Threshold::ZScore { window: 4, limit: 3.0 });
+        assert_eq!(zscore.detect(&series), vec![6]);
+
+        let mad = AnomalyDetector::default().with(Threshold::Mad { window: 4, limit: 3.0 });
+        assert_eq!(mad.detect(&series), vec![6]);
+        assert_eq!(mad.clean(&series), vec![10, 11, 10, 11, 10, 11, 11, 12, 11]);
+    }
+
+    #[test]
+    fn lasting_changes() {
+        // A step down to a new depth is real, so only the rolling
+        // thresholds flag the first reading after it
+        let series = [10, 11, 10, 11, 50, 51, 50, 51];
+
+        let jump = AnomalyDetector::default().with(Threshold::Jump(20.0));
+        assert!(jump.detect(&series).is_empty());
+
+        let zscore = AnomalyDetector::default().with(Threshold::ZScore { window: 4, limit: 3.0 });
+        assert_eq!(zscore.detect(&series), vec![4]);
+
+        // The ends only have one neighbor each, so a jump there might be
+        // a change in depth
+        assert!(jump.detect(&[10, 50, 51, 50]).is_empty());
+        assert!(jump.detect(&[50, 51, 50, 10]).is_empty());
+        assert_eq!(jump.detect(&[50, 10, 50]), vec![1]);
+
+        // No thresholds, no anomalies
+        assert!(AnomalyDetector::default().detect(&series).is_empty());
+    }
+}
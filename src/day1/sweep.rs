@@ -0,0 +1,94 @@
+use crate::error::column_of;
+use crate::{Error, Result};
+
+/// Parse sonar input that may hold several sweeps, one measurement per
+/// line. Blank lines separate one sweep from the next, and anything after
+/// a `#` is a comment. Lines holding only a comment are skipped without
+/// ending the sweep, so sweeps can be annotated.
+///
+/// Unlike skipping whatever doesn't parse, a bad line is an error, with
+/// its line and column.
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day1::{count_measurements, sweep::parse_sweeps};
+/// let input = "# morning\n199\n200 # surfacing?\n208\n\n\n# evening\n210\n200\n207\n";
+/// let sweeps = parse_sweeps(input)?;
+///
+/// assert_eq!(sweeps, vec![vec![199, 200, 208], vec![210, 200, 207]]);
+/// assert_eq!(count_measurements(&sweeps[1]), 1);
+///
+/// let error = parse_sweeps("199\n200\n2o8\n").unwrap_err();
+/// assert_eq!(error.to_string(), "line 3, column 1: Bad measurement: 2o8");
+/// # Ok::<(), aoc2021::Error>(())
+/// ```
+pub fn parse_sweeps(input: &str) -> Result<Vec<Vec<u32>>> {
+    let mut sweeps = vec![];
+    let mut sweep = vec![];
+
+    for (n, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            if !sweep.is_empty() {
+                sweeps.push(std::mem::take(&mut sweep));
+            }
+            continue;
+        }
+
+        if let Some(value) = parse_line(line).map_err(|e| e.at_line(n + 1))? {
+            sweep.push(value);
+        }
+    }
+
+    if !sweep.is_empty() {
+        sweeps.push(sweep);
+    }
+
+    Ok(sweeps)
+}
+
+/// The measurement on a single line, leaving out any comment, or `None` if
+/// there's nothing but a comment. Errors say which column is wrong, but
+/// not which line.
+pub(crate) fn parse_line(line: &str) -> Result<Option<u32>> {
+    let content = line.split('#').next().unwrap_or_default();
+    let mut tokens = content.split_whitespace();
+    let measurement = match tokens.next() {
+        Some(token) => token,
+        None => return Ok(None),
+    };
+
+    let at = |token: &str, message: String| Error::parse(message).at_column(column_of(line, token));
+
+    if let Some(extra) = tokens.next() {
+        return Err(at(extra, format!("Expected one measurement per line, found {} as well", extra)));
+    }
+
+    let value = measurement
+        .parse()
+        .map_err(|_| at(measurement, format!("Bad measurement: {}", measurement)))?;
+
+    Ok(Some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separators() {
+        assert_eq!(parse_sweeps("").unwrap(), Vec::<Vec<u32>>::new());
+        assert_eq!(parse_sweeps("\n\n# nothing here\n\n").unwrap(), Vec::<Vec<u32>>::new());
+        assert_eq!(parse_sweeps("\n1\n2\n\n  \n3\n").unwrap(), vec![vec![1, 2], vec![3]]);
+        assert_eq!(parse_sweeps("1\n# still the same sweep\n2").unwrap(), vec![vec![1, 2]]);
+    }
+
+    #[test]
+    fn bad_lines() {
+        let error = parse_sweeps("1\n2\n  -3\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 3, column 3: Bad measurement: -3");
+
+        let error = parse_sweeps("1\n\n2 3 # two at once\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 3, column 3: Expected one measurement per line, found 3 as well");
+    }
+}
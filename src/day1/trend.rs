@@ -0,0 +1,142 @@
+use std::cmp::Ordering;
+
+/// A stretch of consecutive measurements, from `start` to `end` inclusive
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Streak {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Streak {
+    /// How many measurements are in the streak, which is never none
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.end - self.start + 1
+    }
+}
+
+/// Everything there is to know about how depth changes over a sweep,
+/// gathered in a single pass over the measurements
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day1::trend::{DepthTrendReport, Streak};
+/// let sample = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+/// let report = DepthTrendReport::analyze(sample);
+///
+/// assert_eq!(report.increases, 7);
+/// assert_eq!(report.decreases, 2);
+/// assert_eq!(report.unchanged, 0);
+/// assert_eq!(report.longest_increase, Some(Streak { start: 0, end: 3 }));
+/// assert_eq!(report.longest_decrease, Some(Streak { start: 3, end: 4 }));
+/// assert_eq!(report.max, Some((7, 269)));
+/// assert_eq!(report.min, Some((0, 199)));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct DepthTrendReport<T> {
+    /// How many measurements there were
+    pub count: usize,
+    /// How many measurements were deeper than the one before
+    pub increases: usize,
+    /// How many measurements were shallower than the one before
+    pub decreases: usize,
+    /// How many measurements were the same as the one before
+    pub unchanged: usize,
+    /// The longest strictly increasing stretch, earliest first on a tie
+    pub longest_increase: Option<Streak>,
+    /// The longest strictly decreasing stretch, earliest first on a tie
+    pub longest_decrease: Option<Streak>,
+    /// The index and value of the deepest measurement, earliest first on a tie
+    pub max: Option<(usize, T)>,
+    /// The index and value of the shallowest measurement, earliest first on a tie
+    pub min: Option<(usize, T)>,
+}
+
+impl<T> DepthTrendReport<T> where T: PartialOrd + Copy {
+    pub fn analyze<I>(measurements: I) -> Self where I: IntoIterator<Item = T> {
+        let mut report = Self {
+            count: 0,
+            increases: 0,
+            decreases: 0,
+            unchanged: 0,
+            longest_increase: None,
+            longest_decrease: None,
+            max: None,
+            min: None,
+        };
+
+        let mut previous: Option<T> = None;
+        let mut rising_since = 0;
+        let mut falling_since = 0;
+
+        for (idx, x) in measurements.into_iter().enumerate() {
+            report.count += 1;
+
+            if !matches!(report.max, Some((_, max)) if max >= x) {
+                report.max = Some((idx, x));
+            }
+            if !matches!(report.min, Some((_, min)) if min <= x) {
+                report.min = Some((idx, x));
+            }
+
+            let direction = previous.and_then(|p| x.partial_cmp(&p));
+            match direction {
+                Some(Ordering::Greater) => report.increases += 1,
+                Some(Ordering::Less) => report.decreases += 1,
+                _ if previous.is_some() => report.unchanged += 1,
+                _ => {}
+            }
+
+            if direction != Some(Ordering::Greater) {
+                rising_since = idx;
+            }
+            if direction != Some(Ordering::Less) {
+                falling_since = idx;
+            }
+
+            Self::extend(&mut report.longest_increase, rising_since, idx);
+            Self::extend(&mut report.longest_decrease, falling_since, idx);
+            previous = Some(x);
+        }
+
+        report
+    }
+
+    /// Record the streak from `start` to `end` if it's longer than the best
+    /// so far. Single measurements don't count as a trend.
+    fn extend(best: &mut Option<Streak>, start: usize, end: usize) {
+        let streak = Streak { start, end };
+        if end > start && best.map_or(true, |x| streak.len() > x.len()) {
+            *best = Some(streak);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plateaus() {
+        let report = DepthTrendReport::analyze(vec![5, 5, 4, 3, 3, 2, 1, 0, 1, 2]);
+
+        assert_eq!((report.increases, report.decreases, report.unchanged), (2, 5, 2));
+        assert_eq!(report.longest_decrease, Some(Streak { start: 4, end: 7 }));
+        assert_eq!(report.longest_increase, Some(Streak { start: 7, end: 9 }));
+        assert_eq!(report.max, Some((0, 5)));
+        assert_eq!(report.min, Some((7, 0)));
+    }
+
+    #[test]
+    fn degenerate() {
+        let empty = DepthTrendReport::<u32>::analyze(vec![]);
+        assert_eq!(empty.count, 0);
+        assert_eq!(empty.max, None);
+
+        let flat = DepthTrendReport::analyze(vec![3, 3, 3]);
+        assert_eq!(flat.unchanged, 2);
+        assert_eq!(flat.longest_increase, None);
+        assert_eq!(flat.longest_decrease, None);
+    }
+}
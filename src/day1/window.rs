@@ -0,0 +1,226 @@
+use std::collections::VecDeque;
+
+/// How a window of measurements is boiled down to a single value before
+/// it's compared with the window before it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aggregate {
+    Sum,
+    Mean,
+    Max,
+}
+
+/// Compares each sliding window of measurements with the one before it,
+/// counting how often the aggregate strictly increases.
+///
+/// Neighboring windows share all but their first and last measurements,
+/// so for `Sum` and `Mean` only the measurement dropping out and the one
+/// coming in need comparing. `Max` keeps a queue of the measurements that
+/// could still become the maximum. Either way each measurement is handled
+/// in constant amortized time, however big the window is.
+///
+/// The shortcut for `Sum` and `Mean` assumes addition is exact, so with
+/// floating point measurements the counts can differ from adding up each
+/// window by hand when rounding gets involved.
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day1::window::{Aggregate, WindowComparator};
+/// let mut comparator = WindowComparator::new(3, Aggregate::Sum);
+/// for x in [199, 200, 208, 210, 200, 207, 240, 269, 260, 263] {
+///     comparator.push(x);
+/// }
+///
+/// assert_eq!(comparator.increases(), 5);
+/// ```
+#[derive(Clone, Debug)]
+pub struct WindowComparator<T> {
+    size: usize,
+    aggregate: Aggregate,
+    window: VecDeque<T>,
+    maxima: VecDeque<(usize, T)>,
+    previous_max: Option<T>,
+    seen: usize,
+    increases: usize,
+}
+
+impl<T> WindowComparator<T> where T: PartialOrd + Copy {
+    /// Panics if the window is empty, since there'd be nothing to compare
+    pub fn new(size: usize, aggregate: Aggregate) -> Self {
+        assert!(size > 0, "Windows need at least one measurement in them");
+
+        Self {
+            size,
+            aggregate,
+            window: VecDeque::with_capacity(size + 1),
+            maxima: VecDeque::new(),
+            previous_max: None,
+            seen: 0,
+            increases: 0,
+        }
+    }
+
+    pub fn push(&mut self, measurement: T) {
+        match self.aggregate {
+            Aggregate::Sum | Aggregate::Mean => self.push_sum(measurement),
+            Aggregate::Max => self.push_max(measurement),
+        }
+
+        self.seen += 1;
+    }
+
+    fn push_sum(&mut self, measurement: T) {
+        self.window.push_back(measurement);
+        if self.window.len() > self.size {
+            if let Some(dropped) = self.window.pop_front() {
+                if measurement > dropped {
+                    self.increases += 1;
+                }
+            }
+        }
+    }
+
+    fn push_max(&mut self, measurement: T) {
+        // Anything no bigger than the new measurement can never be the
+        // maximum of a window again, since the new one outlasts it
+        while matches!(self.maxima.back(), Some(&(_, x)) if x <= measurement) {
+            self.maxima.pop_back();
+        }
+        self.maxima.push_back((self.seen, measurement));
+
+        while matches!(self.maxima.front(), Some(&(idx, _)) if idx + self.size <= self.seen) {
+            self.maxima.pop_front();
+        }
+
+        if self.seen + 1 >= self.size {
+            let current = self.maxima[0].1;
+            if matches!(self.previous_max, Some(previous) if current > previous) {
+                self.increases += 1;
+            }
+            self.previous_max = Some(current);
+        }
+    }
+
+    /// How many windows so far had a larger aggregate than the window before
+    pub fn increases(&self) -> usize {
+        self.increases
+    }
+}
+
+/// Count how many times the aggregate of a sliding window of the given
+/// size strictly increases from one window to the next
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day1::window::{count_window_increases, Aggregate};
+/// let sample = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+/// assert_eq!(count_window_increases(&sample, 1, Aggregate::Sum), 7);
+/// assert_eq!(count_window_increases(&sample, 3, Aggregate::Mean), 5);
+/// assert_eq!(count_window_increases(&sample, 3, Aggregate::Max), 3);
+/// assert_eq!(count_window_increases(&sample, 20, Aggregate::Sum), 0);
+/// ```
+pub fn count_window_increases<I>(measurements: I, size: usize, aggregate: Aggregate) -> usize
+where I: IntoIterator, I::Item: PartialOrd + Copy {
+    let mut comparator = WindowComparator::new(size, aggregate);
+    measurements.into_iter().for_each(|x| comparator.push(x));
+    comparator.increases()
+}
+
+/// Same as `count_window_increases`, for when the window size is known
+/// at compile time. The window is kept in an array on the stack rather
+/// than a queue, and `Max` looks over all of it for every measurement, so
+/// this suits small windows best. Panics if `N` is 0.
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day1::window::{count_window_increases_n, Aggregate};
+/// let sample = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+/// assert_eq!(count_window_increases_n::<3, _>(&sample, Aggregate::Sum), 5);
+/// assert_eq!(count_window_increases_n::<3, _>(&sample, Aggregate::Max), 3);
+/// ```
+pub fn count_window_increases_n<const N: usize, I>(measurements: I, aggregate: Aggregate) -> usize
+where I: IntoIterator, I::Item: PartialOrd + Copy {
+    assert!(N > 0, "Windows need at least one measurement in them");
+
+    // The last N measurements, going round so the oldest is the one the
+    // next measurement replaces
+    let mut window: [Option<I::Item>; N] = [None; N];
+    let mut previous_max = None;
+    let mut increases = 0;
+
+    for (seen, measurement) in measurements.into_iter().enumerate() {
+        let dropped = window[seen % N].replace(measurement);
+
+        match aggregate {
+            Aggregate::Sum | Aggregate::Mean => {
+                if matches!(dropped, Some(dropped) if measurement > dropped) {
+                    increases += 1;
+                }
+            }
+            Aggregate::Max if seen + 1 >= N => {
+                let current = window.iter().flatten().fold(measurement, |max, &x| if x > max { x } else { max });
+                if matches!(previous_max, Some(previous) if current > previous) {
+                    increases += 1;
+                }
+                previous_max = Some(current);
+            }
+            Aggregate::Max => {}
+        }
+    }
+
+    increases
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Aggregate every window the slow way, for comparison
+    fn naive(measurements: &[u64], size: usize, aggregate: Aggregate) -> usize {
+        let values: Vec<f64> = measurements
+            .windows(size)
+            .map(|w| match aggregate {
+                Aggregate::Sum => w.iter().sum::<u64>() as f64,
+                Aggregate::Mean => w.iter().sum::<u64>() as f64 / size as f64,
+                Aggregate::Max => *w.iter().max().unwrap() as f64,
+            })
+            .collect();
+
+        values.windows(2).filter(|x| x[1] > x[0]).count()
+    }
+
+    #[test]
+    fn matches_naive() {
+        // A deterministic but jumpy series, with plenty of plateaus
+        let measurements: Vec<u64> = (0..500u64).map(|x| (x * 7919 % 113) / 3).collect();
+
+        for size in [1, 2, 3, 5, 17, 64, 499, 500, 501] {
+            for aggregate in [Aggregate::Sum, Aggregate::Mean, Aggregate::Max] {
+                assert_eq!(
+                    count_window_increases(&measurements, size, aggregate),
+                    naive(&measurements, size, aggregate),
+                    "{:?} over {}", aggregate, size);
+            }
+        }
+    }
+
+    #[test]
+    fn fixed_size() {
+        let measurements: Vec<u64> = (0..500u64).map(|x| (x * 7919 % 113) / 3).collect();
+
+        for aggregate in [Aggregate::Sum, Aggregate::Mean, Aggregate::Max] {
+            assert_eq!(count_window_increases_n::<1, _>(&measurements, aggregate), naive(&measurements, 1, aggregate));
+            assert_eq!(count_window_increases_n::<3, _>(&measurements, aggregate), naive(&measurements, 3, aggregate));
+            assert_eq!(count_window_increases_n::<17, _>(&measurements, aggregate), naive(&measurements, 17, aggregate));
+            assert_eq!(count_window_increases_n::<501, _>(&measurements, aggregate), 0);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty_window() {
+        WindowComparator::<u32>::new(0, Aggregate::Sum);
+    }
+}
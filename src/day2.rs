@@ -1,15 +1,142 @@
+use std::fmt::{self, Debug};
 use std::str::FromStr;
-use eyre::{Result, WrapErr};
+use crate::error::column_of;
+use crate::{Error, Result, Solution};
 
-#[derive(Debug, PartialEq, Eq)]
+pub mod optimizer;
+pub mod planner;
+pub mod policy;
+pub mod script;
+pub mod submarine;
+pub mod trajectory;
+
+pub use policy::Policy;
+pub use script::Script;
+pub use submarine::{navigate, try_navigate, Bearing3d, DragBearing, Submarine};
+pub use trajectory::{State, Trajectory};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Command {
     Forward(u32),
+    Back(u32),
     Down(u32),
     Up(u32),
+    /// Turn anticlockwise, seen from above, by a number of degrees
+    Left(u32),
+    /// Turn clockwise, seen from above, by a number of degrees
+    Right(u32),
+}
+
+impl Command {
+    /// The named command, moving by a signed delta. A negative delta turns
+    /// the command around, so `down -3` is the same as `up 3`. Returns
+    /// `None` if there's no such command, or the delta doesn't fit in a
+    /// `u32` either way round.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aoc2021::day2::Command;
+    /// assert_eq!(Command::signed("down", -3), Some(Command::Up(3)));
+    /// assert_eq!(Command::signed("forward", -2), Some(Command::Back(2)));
+    /// assert_eq!(Command::signed("back", 2), Some(Command::Back(2)));
+    /// assert_eq!(Command::signed("left", -90), Some(Command::Right(90)));
+    /// assert_eq!(Command::signed("sideways", 2), None);
+    /// ```
+    pub fn signed(name: &str, delta: i64) -> Option<Self> {
+        let size = u32::try_from(delta.unsigned_abs()).ok()?;
+        let command = match (name, delta < 0) {
+            ("forward", false) | ("back", true) => Self::Forward(size),
+            ("back", false) | ("forward", true) => Self::Back(size),
+            ("down", false) | ("up", true) => Self::Down(size),
+            ("up", false) | ("down", true) => Self::Up(size),
+            ("left", false) | ("right", true) => Self::Left(size),
+            ("right", false) | ("left", true) => Self::Right(size),
+            _ => return None,
+        };
+
+        Some(command)
+    }
+
+    /// The name the command goes by in scripts
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Forward(_) => "forward",
+            Self::Back(_) => "back",
+            Self::Down(_) => "down",
+            Self::Up(_) => "up",
+            Self::Left(_) => "left",
+            Self::Right(_) => "right",
+        }
+    }
+
+    /// How far the command moves
+    pub fn delta(&self) -> u32 {
+        match *self {
+            Self::Forward(x) | Self::Back(x) | Self::Down(x) | Self::Up(x) | Self::Left(x) | Self::Right(x) => x,
+        }
+    }
+
+    /// The signed horizontal and vertical change the command asks for.
+    /// Turns don't ask for either.
+    pub fn vector(&self) -> (i64, i64) {
+        match *self {
+            Self::Forward(x) => (x as i64, 0),
+            Self::Back(x) => (-(x as i64), 0),
+            Self::Down(x) => (0, x as i64),
+            Self::Up(x) => (0, -(x as i64)),
+            Self::Left(_) | Self::Right(_) => (0, 0),
+        }
+    }
+
+    /// The signed change in heading the command asks for, in degrees
+    /// anticlockwise
+    pub fn turn(&self) -> i64 {
+        match *self {
+            Self::Left(x) => x as i64,
+            Self::Right(x) => -(x as i64),
+            _ => 0,
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    /// Writes the command the way scripts spell it, so it parses back
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aoc2021::day2::Command;
+    /// assert_eq!(Command::Back(3).to_string(), "back 3");
+    /// assert_eq!("back 3".parse::<Command>()?, Command::Back(3));
+    /// # Ok::<(), aoc2021::Error>(())
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&format!("{} {}", self.name(), self.delta()))
+    }
+}
+
+fn overflow(cmd: Command, from: impl Debug, policy: Policy) -> Error {
+    Error::Overflow(format!("{} from {:?} is out of range with {:?} arithmetic", cmd, from, policy))
+}
+
+/// Parse a command delta, which may have a sign. Either way round, it has
+/// to fit in a `u32`.
+fn parse_delta(token: &str) -> Result<i64> {
+    let delta: i64 = token
+        .parse()
+        .map_err(|_| Error::parse(format!("Command delta wasn't an integer: {}", token)))?;
+
+    if delta.unsigned_abs() > u32::MAX as u64 {
+        return Err(Error::parse(format!("Command delta is too big: {}", token)));
+    }
+
+    Ok(delta)
 }
 
 impl FromStr for Command {
-    type Err = eyre::Report;
+    type Err = Error;
 
     /// Attempts to parse a `Command` from a simple text-encoded
     /// representation.
@@ -23,6 +150,12 @@ impl FromStr for Command {
     /// assert_eq!(Command::from_str("forward 1")?, Command::Forward(1));
     /// assert_eq!(Command::from_str("up 1")?, Command::Up(1));
     /// assert_eq!(Command::from_str("down 3")?, Command::Down(3));
+    /// assert_eq!(Command::from_str("back 2")?, Command::Back(2));
+    /// assert_eq!(Command::from_str("left 90")?, Command::Left(90));
+    ///
+    /// // Signed deltas turn the command around
+    /// assert_eq!(Command::from_str("down -3")?, Command::Up(3));
+    /// assert_eq!(Command::from_str("forward +2")?, Command::Forward(2));
     ///
     /// assert!(Command::from_str("backwards 3").is_err());
     /// assert!(Command::from_str("forward x").is_err());
@@ -30,25 +163,25 @@ impl FromStr for Command {
     /// assert!(Command::from_str("forward 1 2").is_err());
     /// assert!(Command::from_str("forward 1.2").is_err());
     ///
-    /// # Ok::<(), eyre::Report>(())
+    /// # Ok::<(), aoc2021::Error>(())
     /// ```
     fn from_str(s: &str) -> Result<Self> {
         let tokens: Vec<&str> = s.split_ascii_whitespace().collect();
-        eyre::ensure!(tokens.len() == 2, "Commands are strings with two whitespace separated tokens");
+        if tokens.len() != 2 {
+            return Err(Error::parse("Commands are strings with two whitespace separated tokens"));
+        }
 
-        let delta: u32 = tokens[1].parse().wrap_err("Command delta wasn't a positive integer")?;
+        let delta = parse_delta(tokens[1]).map_err(|e| e.at_column(column_of(s, tokens[1])))?;
 
-        match tokens[0] {
-            "forward" => Ok(Self::Forward(delta)),
-            "down" => Ok(Self::Down(delta)),
-            "up" => Ok(Self::Up(delta)),
-            unknown => eyre::bail!("Not sure what to make of command: {}", unknown),
-        }
+        Self::signed(tokens[0], delta).ok_or_else(|| {
+            Error::parse(format!("Not sure what to make of command: {}", tokens[0])).at_column(column_of(s, tokens[0]))
+        })
     }
 }
 
-#[derive(Debug, PartialEq)]
-pub struct Position(pub u32, pub u32);
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position(pub i64, pub i64);
 
 impl Position {
     /// Executes a single command against this position, and
@@ -70,20 +203,63 @@ impl Position {
     ///
     /// // Don't let our submarine fly, because that's weird
     /// assert_eq!(Position(0,0).execute(Command::Up(1)), Position(0,0));
+    ///
+    /// // Nor back up past where it started
+    /// assert_eq!(Position(2,0).execute(Command::Back(3)), Position(0,0));
     /// ```
     pub fn execute(self, cmd: Command) -> Self {
-        match cmd {
-            Command::Forward(x) => Self(self.0 + x, self.1),
-            Command::Down(x) => Self(self.0, self.1 + x),
-            Command::Up(x) => Self(self.0, self.1.saturating_sub(x)),
-        }
+        self.try_execute(cmd, Policy::Saturating).expect("Saturating arithmetic can't fail")
+    }
+
+    /// Executes a single command with the given numeric policy, failing
+    /// if the policy doesn't allow where the command would take us
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aoc2021::day2::{Command, Policy, Position};
+    /// assert_eq!(Position(0, 2).try_execute(Command::Up(3), Policy::Signed)?, Position(0, -1));
+    /// assert_eq!(Position(0, 2).try_execute(Command::Up(3), Policy::Wrapping)?, Position(0, u32::MAX as i64));
+    /// assert!(Position(0, 2).try_execute(Command::Up(3), Policy::Checked).is_err());
+    /// # Ok::<(), aoc2021::Error>(())
+    /// ```
+    pub fn try_execute(self, cmd: Command, policy: Policy) -> Result<Self> {
+        let (dx, dy) = cmd.vector();
+        let moved = |from: i64, by: i64| {
+            policy
+                .apply(from as i128 + by as i128)
+                .ok_or_else(|| overflow(cmd, self, policy))
+        };
+
+        Ok(Self(moved(self.0, dx)?, moved(self.1, dy)?))
+    }
+
+    /// Executes every command in a script, in order
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aoc2021::day2::{Position, Script};
+    /// let script: Script = "repeat 3 { forward 2 down 1 }".parse()?;
+    /// assert_eq!(Position::default().run(&script), Position(6, 3));
+    /// # Ok::<(), aoc2021::Error>(())
+    /// ```
+    pub fn run(self, script: &Script) -> Self {
+        script.commands().fold(self, Self::execute)
+    }
+
+    /// Executes every command in a script with the given numeric policy,
+    /// stopping at the first that fails
+    pub fn try_run(self, script: &Script, policy: Policy) -> Result<Self> {
+        script.commands().try_fold(self, |acc, cmd| acc.try_execute(cmd, policy))
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bearing {
     pub position: Position,
-    pub aim: u32,
+    pub aim: i64,
 }
 
 impl Default for Bearing {
@@ -112,26 +288,82 @@ impl Bearing {
     ///   .execute(Command::Forward(2));
     /// assert_eq!(b.position, Position(15, 60));
     ///
+    /// // Backing up retraces the slope we'd have gone forward along
+    /// assert_eq!(b.execute(Command::Back(2)).position, Position(13, 40));
     /// ```
     pub fn execute(self, cmd: Command) -> Self {
-        match cmd {
-            Command::Down(x) => Self { aim: self.aim + x, ..self },
-            Command::Up(x) => Self { aim: self.aim.saturating_sub(x), ..self },
-            Command::Forward(x) => {
-                let position = Position(self.position.0 + x, self.position.1 + (x * self.aim));
-                Self { position, ..self }
-            }
-        }
+        self.try_execute(cmd, Policy::Saturating).expect("Saturating arithmetic can't fail")
+    }
+
+    /// Execute a single command with the given numeric policy, failing if
+    /// the policy doesn't allow where the command would take us
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aoc2021::day2::{Bearing, Command, Policy, Position};
+    /// let rising = Bearing { position: Position(0, 0), aim: -2 };
+    /// assert_eq!(rising.try_execute(Command::Forward(3), Policy::Signed)?.position, Position(3, -6));
+    /// assert_eq!(rising.try_execute(Command::Forward(3), Policy::Saturating)?.position, Position(3, 0));
+    ///
+    /// let steep = Bearing { position: Position(0, 0), aim: 1 << 20 };
+    /// assert!(steep.try_execute(Command::Forward(1 << 12), Policy::Checked).is_err());
+    /// assert_eq!(steep.try_execute(Command::Forward(1 << 12), Policy::Wrapping)?.position, Position(1 << 12, 0));
+    /// # Ok::<(), aoc2021::Error>(())
+    /// ```
+    pub fn try_execute(self, cmd: Command, policy: Policy) -> Result<Self> {
+        let (dx, daim) = cmd.vector();
+        let apply = |value: i128| policy.apply(value).ok_or_else(|| overflow(cmd, self, policy));
+
+        let Position(x, y) = self.position;
+        let position = Position(apply(x as i128 + dx as i128)?, apply(y as i128 + dx as i128 * self.aim as i128)?);
+        let aim = apply(self.aim as i128 + daim as i128)?;
+
+        Ok(Self { position, aim })
+    }
+
+    /// Executes every command in a script, in order
+    pub fn run(self, script: &Script) -> Self {
+        script.commands().fold(self, Self::execute)
+    }
+
+    /// Executes every command in a script with the given numeric policy,
+    /// stopping at the first that fails
+    pub fn try_run(self, script: &Script, policy: Policy) -> Result<Self> {
+        script.commands().try_fold(self, |acc, cmd| acc.try_execute(cmd, policy))
+    }
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    type Input = Script;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        input.parse()
+    }
+
+    fn part1(input: &Self::Input) -> Result<u64> {
+        let Position(x, y) = navigate(Position::default(), input.commands());
+
+        Ok(x as u64 * y as u64)
+    }
+
+    fn part2(input: &Self::Input) -> Result<u64> {
+        let Bearing { position: Position(x, y), .. } = navigate(Bearing::default(), input.commands());
+
+        Ok(x as u64 * y as u64)
     }
 }
 
 #[cfg(test)]
 mod answers {
     use super::*;
+    use crate::inputs::{Inputs, Kind, DEFAULT_INPUT_DIR};
 
     #[test]
     fn puzzle1() {
-        let position = include_str!("./input/day2")
+        let position = Inputs::new(DEFAULT_INPUT_DIR).load(2, Kind::Puzzle).unwrap()
             .lines()
             .fold(Position(0,0), |acc, x| acc.execute(x.parse().unwrap()));
 
@@ -140,10 +372,47 @@ mod answers {
 
     #[test]
     fn puzzle2() {
-        let Bearing { position, .. } = include_str!("./input/day2")
+        let Bearing { position, .. } = Inputs::new(DEFAULT_INPUT_DIR).load(2, Kind::Puzzle).unwrap()
             .lines()
             .fold(Bearing::default(), |acc, x| acc.execute(x.parse().unwrap()));
 
         assert_eq!(position.0 * position.1, 1451210346);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for delta in [0, 1, 42, u32::MAX] {
+            for cmd in [Command::Forward(delta), Command::Back(delta), Command::Down(delta), Command::Up(delta)] {
+                assert_eq!(cmd.to_string().parse::<Command>().unwrap(), cmd);
+            }
+        }
+
+        let script: Script = "repeat 2 { down 3 }\nforward -4".parse().unwrap();
+        let written: Vec<String> = script.commands().map(|x| x.to_string()).collect();
+        assert_eq!(written.join("\n"), "down 3\ndown 3\nback 4");
+        assert_eq!(format!("[{:>8}]", Command::Up(1)), "[    up 1]");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialization() {
+        let commands = vec![Command::Forward(5), Command::Back(2), Command::Down(u32::MAX), Command::Up(0)];
+        let json = serde_json::to_string(&commands).unwrap();
+        assert_eq!(json, r#"[{"Forward":5},{"Back":2},{"Down":4294967295},{"Up":0}]"#);
+        assert_eq!(serde_json::from_str::<Vec<Command>>(&json).unwrap(), commands);
+
+        let bytes = bincode::serialize(&commands).unwrap();
+        assert_eq!(bincode::deserialize::<Vec<Command>>(&bytes).unwrap(), commands);
+
+        let bearing = Bearing { position: Position(15, -60), aim: 10 };
+        let json = serde_json::to_string(&bearing).unwrap();
+        assert_eq!(json, r#"{"position":[15,-60],"aim":10}"#);
+        assert_eq!(serde_json::from_str::<Bearing>(&json).unwrap(), bearing);
+        assert_eq!(bincode::deserialize::<Bearing>(&bincode::serialize(&bearing).unwrap()).unwrap(), bearing);
+    }
+}
@@ -0,0 +1,236 @@
+use std::mem::discriminant;
+use crate::Result;
+use super::planner::push;
+use super::{try_navigate, Command, Policy, Submarine};
+
+/// The movement models the optimizer knows how to rewrite scripts for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Model {
+    Position,
+    Bearing,
+}
+
+/// Which of the submarine's controls a command works
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+/// Rewrite a script into a shorter one that leaves the submarine in the
+/// same state, from wherever it starts.
+///
+/// How far that can go depends on the numeric policy. With `Signed` and
+/// `Wrapping` arithmetic, moves add up in any order, so for `Position`
+/// the whole script boils down to one net move along each axis. For
+/// `Bearing`, each run of aim changes and each run of moves forward and
+/// back is replaced by its net effect, since moves only commute while the
+/// aim is held steady. Runs that cancel out disappear, letting their
+/// neighbors join up.
+///
+/// Clamping and errors at the ends of the range make that unsafe with
+/// `Saturating` and `Checked` arithmetic, where `down 5, up 3` isn't the
+/// same as `down 2` near the surface. There, only neighboring commands in
+/// the same direction are added together, as long as they fit in a
+/// `u32`, and commands that don't move at all are dropped. Neither model
+/// has a heading, so that includes turns.
+///
+/// With `Signed` arithmetic, the rewritten script can succeed where the
+/// original would have overflowed an `i64` along the way.
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day2::{Command::*, Policy};
+/// use aoc2021::day2::optimizer::{optimize, Model};
+/// let script = [Forward(1), Down(5), Up(3), Forward(2), Back(2), Down(1), Forward(4)];
+///
+/// assert_eq!(optimize(&script, Model::Position, Policy::Signed), [Forward(5), Down(3)]);
+/// assert_eq!(optimize(&script, Model::Bearing, Policy::Signed), [Forward(1), Down(3), Forward(4)]);
+/// assert_eq!(optimize(&[Down(5), Down(3), Forward(0), Up(1)], Model::Bearing, Policy::Saturating), [Down(8), Up(1)]);
+/// ```
+pub fn optimize(commands: &[Command], model: Model, policy: Policy) -> Vec<Command> {
+    match policy {
+        Policy::Signed | Policy::Wrapping => regroup(commands, model, policy),
+        Policy::Saturating | Policy::Checked => merge_neighbors(commands),
+    }
+}
+
+/// Whether two scripts leave a submarine in the same state, starting from
+/// the given one. Fails if either script does.
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day2::{Bearing, Command::*, Policy, Position};
+/// use aoc2021::day2::optimizer::equivalent;
+/// let a = [Forward(2), Down(3)];
+/// let b = [Down(3), Forward(2)];
+///
+/// assert!(equivalent(Position::default(), &a, &b, Policy::Saturating)?);
+/// assert!(!equivalent(Bearing::default(), &a, &b, Policy::Saturating)?);
+/// assert!(equivalent(Position::default(), &[Up(1)], &[], Policy::Checked).is_err());
+/// # Ok::<(), aoc2021::Error>(())
+/// ```
+pub fn equivalent<S>(start: S, a: &[Command], b: &[Command], policy: Policy) -> Result<bool> where S: Submarine + PartialEq {
+    let a = try_navigate(start, a.iter().copied(), policy)?;
+    let b = try_navigate(start, b.iter().copied(), policy)?;
+
+    Ok(a == b)
+}
+
+fn regroup(commands: &[Command], model: Model, policy: Policy) -> Vec<Command> {
+    const RANGE: i128 = 1 << 32;
+
+    // Wrapping arithmetic can't tell apart amounts a whole range apart
+    let normalize = |net: i128| match policy {
+        Policy::Wrapping => match net.rem_euclid(RANGE) {
+            n if n > RANGE / 2 => n - RANGE,
+            n => n,
+        },
+        _ => net,
+    };
+
+    let mut groups: Vec<(Axis, i128)> = vec![];
+    for cmd in commands {
+        let (axis, amount) = match cmd.vector() {
+            (0, 0) => continue,
+            (0, dy) => (Axis::Vertical, dy),
+            (dx, _) => (Axis::Horizontal, dx),
+        };
+
+        let group = match model {
+            Model::Position => groups.iter().position(|&(x, _)| x == axis),
+            Model::Bearing => groups.len().checked_sub(1).filter(|&i| groups[i].0 == axis),
+        };
+
+        match group {
+            Some(i) => groups[i].1 = normalize(groups[i].1 + amount as i128),
+            None => groups.push((axis, amount as i128)),
+        }
+
+        // A run that cancels out lets the runs either side of it join up
+        if model == Model::Bearing && groups.last().map(|x| x.1) == Some(0) {
+            groups.pop();
+        }
+    }
+
+    let mut optimized = vec![];
+    for (axis, net) in groups {
+        let command: fn(u32) -> Command = match (axis, net < 0) {
+            (Axis::Horizontal, false) => Command::Forward,
+            (Axis::Horizontal, true) => Command::Back,
+            (Axis::Vertical, false) => Command::Down,
+            (Axis::Vertical, true) => Command::Up,
+        };
+
+        push(&mut optimized, command, net.unsigned_abs());
+    }
+
+    optimized
+}
+
+fn merge_neighbors(commands: &[Command]) -> Vec<Command> {
+    let mut merged: Vec<Command> = vec![];
+
+    for &cmd in commands {
+        if cmd.vector() == (0, 0) {
+            continue;
+        }
+
+        match merged.last_mut() {
+            Some(last) if discriminant(last) == discriminant(&cmd) => match last.delta().checked_add(cmd.delta()) {
+                Some(total) => *last = resized(cmd, total),
+                None => merged.push(cmd),
+            },
+            _ => merged.push(cmd),
+        }
+    }
+
+    merged
+}
+
+/// The same kind of command, with a different delta
+fn resized(cmd: Command, delta: u32) -> Command {
+    match cmd {
+        Command::Forward(_) => Command::Forward(delta),
+        Command::Back(_) => Command::Back(delta),
+        Command::Down(_) => Command::Down(delta),
+        Command::Up(_) => Command::Up(delta),
+        Command::Left(_) => Command::Left(delta),
+        Command::Right(_) => Command::Right(delta),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day2::{Bearing, Position};
+
+    /// Scripts with plenty of back and forth, from a simple linear
+    /// congruential generator
+    fn scripts(big: bool) -> Vec<Vec<Command>> {
+        let mut seed: u64 = 2021;
+        let mut next = move |n: u64| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) % n
+        };
+
+        (0..200)
+            .map(|_| {
+                (0..next(24))
+                    .map(|_| {
+                        let delta = match next(10) {
+                            0 if big => u32::MAX - next(3) as u32,
+                            0 => 0,
+                            _ => next(6) as u32,
+                        };
+                        [Command::Forward, Command::Back, Command::Down, Command::Up][next(4) as usize](delta)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn check<S: Submarine + PartialEq + std::fmt::Debug>(starts: &[S], model: Model, policy: Policy, big: bool) {
+        for script in scripts(big) {
+            let optimized = optimize(&script, model, policy);
+            assert!(optimized.len() <= script.len());
+
+            for &start in starts {
+                let before = try_navigate(start, script.iter().copied(), policy).ok();
+                let after = try_navigate(start, optimized.iter().copied(), policy).ok();
+                assert_eq!(before, after, "{:?} optimized to {:?} from {:?}", script, optimized, start);
+            }
+        }
+    }
+
+    #[test]
+    fn equivalence() {
+        let positions = [Position(0, 0), Position(3, 2), Position(u32::MAX as i64 - 4, 7)];
+        let bearings = [
+            Bearing::default(),
+            Bearing { position: Position(5, 5), aim: 2 },
+            Bearing { position: Position(0, 0), aim: u32::MAX as i64 - 3 },
+        ];
+
+        for policy in [Policy::Saturating, Policy::Checked, Policy::Wrapping] {
+            for big in [false, true] {
+                check(&positions, Model::Position, policy, big);
+                check(&bearings, Model::Bearing, policy, big);
+            }
+        }
+
+        check(&positions, Model::Position, Policy::Signed, true);
+        check(&bearings, Model::Bearing, Policy::Signed, false);
+    }
+
+    #[test]
+    fn cancelling_runs() {
+        let script = [Command::Down(2), Command::Forward(3), Command::Back(3), Command::Down(1)];
+
+        assert_eq!(optimize(&script, Model::Bearing, Policy::Signed), [Command::Down(3)]);
+        assert_eq!(optimize(&script, Model::Bearing, Policy::Checked), script);
+        assert_eq!(optimize(&[Command::Down(u32::MAX), Command::Down(2)], Model::Position, Policy::Wrapping), [Command::Down(1)]);
+    }
+}
@@ -0,0 +1,355 @@
+use crate::{Error, Result};
+use super::{try_navigate, Bearing, Command, Policy, Position, Submarine};
+
+/// What a plan for the `Bearing` model should keep as small as possible
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Objective {
+    /// The fewest commands
+    MinLength,
+    /// The smallest sum of all the commands' deltas
+    MinTotalDelta,
+}
+
+/// The shortest script that takes a `Position` from the origin to the
+/// target, which is also the one with the smallest total delta. Reaching
+/// negative coordinates takes the `Signed` policy.
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day2::{Command, Policy, Position};
+/// use aoc2021::day2::planner::plan_position;
+///
+/// assert_eq!(plan_position(Position(15, 10), Policy::Saturating)?, [Command::Forward(15), Command::Down(10)]);
+/// assert_eq!(plan_position(Position(0, -3), Policy::Signed)?, [Command::Up(3)]);
+/// assert!(plan_position(Position(0, -3), Policy::Checked).is_err());
+/// # Ok::<(), aoc2021::Error>(())
+/// ```
+pub fn plan_position(target: Position, policy: Policy) -> Result<Vec<Command>> {
+    let Position(h, d) = target;
+    let mut commands = vec![];
+
+    push(&mut commands, if h < 0 { Command::Back } else { Command::Forward }, h.unsigned_abs() as u128);
+    push(&mut commands, if d < 0 { Command::Up } else { Command::Down }, d.unsigned_abs() as u128);
+
+    validate(Position::default(), commands, target, policy)
+}
+
+/// A script that takes a `Bearing` from the surface with no aim to the
+/// target position, whatever its aim at the end.
+///
+/// Depth only changes while moving, so reaching any depth takes at least
+/// one step forward or back. Otherwise:
+///
+/// * With `MinLength`, if the horizontal distance `h` divides the depth
+///   `d`, it's `down d/h, forward h`. If not, three commands are needed,
+///   and `forward h-1, down d, forward 1` will do. Getting deeper without
+///   going anywhere takes going forward and back at different aims.
+/// * With `MinTotalDelta`, it goes `n` further than `h` and backs up `n`
+///   again at a lower aim, for whichever `n` is cheapest, often 0. With
+///   the `Signed` and `Wrapping` policies it backs up past the start
+///   first, at no aim, which is cheaper than turning around at the end.
+///   The plans are as small as possible for every policy but `Wrapping`,
+///   where going round the range can sometimes do better.
+///
+/// Deltas too big for a `u32` are split across several commands. With
+/// `MinLength`, the `MinTotalDelta` plan is used instead if that makes it
+/// shorter.
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day2::{Command::*, Policy, Position};
+/// use aoc2021::day2::planner::{plan_bearing, Objective};
+/// let target = Position(15, 60);
+///
+/// assert_eq!(plan_bearing(target, Objective::MinLength, Default::default())?, [Down(4), Forward(15)]);
+///
+/// let target = Position(10, 25);
+/// assert_eq!(plan_bearing(target, Objective::MinLength, Default::default())?, [Forward(9), Down(25), Forward(1)]);
+/// assert_eq!(plan_bearing(target, Objective::MinTotalDelta, Default::default())?, [Down(2), Forward(5), Down(1), Forward(5)]);
+///
+/// let target = Position(1, 100);
+/// assert_eq!(plan_bearing(target, Objective::MinTotalDelta, Policy::Checked)?, [Down(10), Forward(10), Up(10), Back(9)]);
+/// assert_eq!(plan_bearing(target, Objective::MinTotalDelta, Policy::Signed)?, [Back(7), Down(12), Forward(4), Down(1), Forward(4)]);
+/// # Ok::<(), aoc2021::Error>(())
+/// ```
+pub fn plan_bearing(target: Position, objective: Objective, policy: Policy) -> Result<Vec<Command>> {
+    let Position(h, d) = target;
+    let (x, y) = (h.unsigned_abs() as u128, d.unsigned_abs() as u128);
+    let negative = matches!(policy, Policy::Signed | Policy::Wrapping);
+
+    // Plan for the mirror image down and to the right, then turn it
+    // around. Backing up changes depth by the opposite of the aim.
+    let forward: fn(u32) -> Command = if h < 0 { Command::Back } else { Command::Forward };
+    let back: fn(u32) -> Command = if h < 0 { Command::Forward } else { Command::Back };
+    let down: fn(u32) -> Command = if (d < 0) != (h < 0) { Command::Up } else { Command::Down };
+    let up: fn(u32) -> Command = if (d < 0) != (h < 0) { Command::Down } else { Command::Up };
+
+    // Going past the target and backing up again, for the smallest total
+    let roundabout = || {
+        let (extra, aim) = detour(x, y, negative);
+        let mut moves = vec![];
+        if negative {
+            moves.push((back, extra));
+        }
+
+        // Spread the depth needed over the steps forward, with the aim
+        // going up by one part way through if it doesn't divide evenly
+        let (ahead, depth) = (x + extra, y + aim * extra);
+        let (q, r) = (depth / ahead, depth % ahead);
+        moves.extend([(down, q), (forward, ahead - r), (down, r.min(1)), (forward, r)]);
+
+        if !negative && extra > 0 {
+            let top = q + r.min(1);
+            moves.extend([(up, top.saturating_sub(aim)), (down, aim.saturating_sub(top)), (back, extra)]);
+        }
+
+        moves
+    };
+
+    let mut plans = vec![];
+    match objective {
+        _ if y == 0 => plans.push(vec![(forward, x)]),
+        Objective::MinLength if x == 0 && negative => plans.push(vec![(back, 1), (down, y), (forward, 1)]),
+        Objective::MinLength if x == 0 => plans.push(vec![(down, y), (forward, 1), (up, y), (back, 1)]),
+        Objective::MinLength if y % x == 0 => plans.push(vec![(down, y / x), (forward, x)]),
+        Objective::MinLength => plans.push(vec![(forward, x - 1), (down, y), (forward, 1)]),
+        Objective::MinTotalDelta => plans.push(roundabout()),
+    }
+
+    // An aim too big for one command takes several, and then the long way
+    // round can be shorter
+    if objective == Objective::MinLength && y > u32::MAX as u128 {
+        plans.push(roundabout());
+    }
+
+    plans.sort_by_key(|moves| moves.iter().map(|&(_, amount)| (amount + u32::MAX as u128 - 1) / u32::MAX as u128).sum::<u128>());
+    plans
+        .into_iter()
+        .find_map(|moves| {
+            let mut commands = vec![];
+            for (command, amount) in moves {
+                push(&mut commands, command, amount);
+            }
+
+            validate(Bearing::default(), commands, target, policy).ok()
+        })
+        .ok_or_else(|| unreachable(target, policy))
+}
+
+/// How far past `x` to go and then back up for the smallest total delta
+/// reaching `(x, y)`, and the aim to back up with. If going negative is
+/// allowed, backing up happens first and the aim is always 0.
+///
+/// Going `n` further, with the aim topping out at `m` and backing up at
+/// `m - e`, costs `x + 2n + m + e`, and reaches any depth up to `m*x + e*n`.
+/// Backing up first at no aim costs `x + 2n + m` and reaches `m*(x + n)`.
+/// Turning around is never better than not backing up at all unless
+/// `n >= x`, and then `e` may as well be as near `m` as rounding allows.
+/// Every cost is at least `x + 2n + c*y/(x + n)` for `c` of 1 or
+/// 2, and that's smallest around `x + n = sqrt(c*y/2)`, so it's enough to
+/// search out from there until the bound is worse than the best so far.
+fn detour(x: u128, y: u128, negative: bool) -> (u128, u128) {
+    let c = if negative { 1 } else { 2 };
+
+    // The part of the cost after `x` of going `n` further, and the aim to
+    // back up with
+    let cost = |n: u128| -> (u128, u128) {
+        let ahead = x + n;
+        let top = (y + ahead - 1) / ahead;
+        if negative {
+            return (2 * n + top, 0);
+        } else if n == 0 {
+            return (top, 0);
+        }
+
+        // Back up at no aim, or turn around one short of the top if the
+        // steps forward can make up the difference
+        let mut best = (2 * n + 2 * top, 0);
+        if x > 0 && top > 0 {
+            let e = top - 1;
+            let m = e.max((y.saturating_sub(e * n) + x - 1) / x);
+            best = best.min((2 * n + m + e, m - e));
+        }
+
+        best
+    };
+
+    // (cost, n, aim) for the best detour so far
+    let mut best = (u128::MAX, 0, 0);
+    let mut consider = |n: u128| {
+        let (total, aim) = cost(n);
+        if total < best.0 {
+            best = (total, n, aim);
+        }
+
+        2 * n * (x + n) + c * y >= best.0 * (x + n)
+    };
+
+    let first = if negative { u128::from(x == 0) } else { x.max(1) };
+    if !negative && x > 0 {
+        consider(0);
+    }
+
+    let middle = first.max((isqrt(c * y / 2) + 1).saturating_sub(x));
+    for n in middle.. {
+        if consider(n) {
+            break;
+        }
+    }
+
+    for n in (first..middle).rev() {
+        if consider(n) {
+            break;
+        }
+    }
+
+    (best.1, best.2)
+}
+
+fn isqrt(value: u128) -> u128 {
+    let mut root = (value as f64).sqrt() as u128;
+    while root * root > value {
+        root -= 1;
+    }
+
+    while (root + 1) * (root + 1) <= value {
+        root += 1;
+    }
+
+    root
+}
+
+/// Add commands moving by `amount` in total, split up as needed to fit
+/// in a `u32`
+pub(super) fn push(commands: &mut Vec<Command>, command: fn(u32) -> Command, mut amount: u128) {
+    while amount > 0 {
+        let delta = amount.min(u32::MAX as u128);
+        commands.push(command(delta as u32));
+        amount -= delta;
+    }
+}
+
+/// Replay a plan to make sure it really does reach the target
+fn validate<S: Submarine>(start: S, commands: Vec<Command>, target: Position, policy: Policy) -> Result<Vec<Command>> {
+    match try_navigate(start, commands.iter().copied(), policy) {
+        Ok(end) if end.position() == target => Ok(commands),
+        _ => Err(unreachable(target, policy)),
+    }
+}
+
+fn unreachable(target: Position, policy: Policy) -> Error {
+    Error::NoAnswer(format!("There's no way to reach {:?} with {:?} arithmetic", target, policy))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet, VecDeque};
+    use super::*;
+
+    const STEPS: [fn(u32) -> Command; 4] = [Command::Forward, Command::Back, Command::Down, Command::Up];
+
+    fn total(commands: &[Command]) -> u64 {
+        commands.iter().map(|x| x.delta() as u64).sum()
+    }
+
+    /// The smallest total delta that takes a bearing to each position it can
+    /// reach without leaving the box `size` wide, `size` deep and with up to
+    /// `size` aim either way. Bigger deltas are the same as several steps of
+    /// 1, so it's enough to try those breadth first.
+    fn smallest_totals(size: i64, policy: Policy) -> HashMap<(i64, i64), u64> {
+        let mut totals = HashMap::new();
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::from(vec![(Bearing::default(), 0)]);
+
+        while let Some((bearing, total)) = queue.pop_front() {
+            let Position(h, d) = bearing.position;
+            totals.entry((h, d)).or_insert(total);
+
+            for step in STEPS {
+                let next = match bearing.try_execute(step(1), policy) {
+                    Ok(next) => next,
+                    Err(_) => continue,
+                };
+
+                let Position(h, d) = next.position;
+                if h.abs() <= size && d.abs() <= size && next.aim.abs() <= size && seen.insert((h, d, next.aim)) {
+                    queue.push_back((next, total + 1));
+                }
+            }
+        }
+
+        totals
+    }
+
+    /// Whether any script of at most `len` commands, each with a delta of
+    /// at most `limit`, takes a bearing to the target
+    fn reachable(from: Bearing, target: Position, len: usize, limit: u32, policy: Policy) -> bool {
+        if from.position == target {
+            return true;
+        }
+
+        len > 0
+            && STEPS.iter().any(|step| {
+                (1..=limit).any(|delta| match from.try_execute(step(delta), policy) {
+                    Ok(next) => reachable(next, target, len - 1, limit, policy),
+                    Err(_) => false,
+                })
+            })
+    }
+
+    #[test]
+    fn fewest_commands() {
+        for policy in [Policy::Saturating, Policy::Checked, Policy::Signed] {
+            for h in -4..=4_i64 {
+                for d in -12..=12_i64 {
+                    let target = Position(h, d);
+                    let limit = h.abs().max(d.abs()) as u32;
+
+                    let fewer = match plan_bearing(target, Objective::MinLength, policy) {
+                        Ok(plan) if plan.is_empty() => continue,
+                        Ok(plan) => plan.len() - 1,
+                        Err(_) => 3,
+                    };
+
+                    assert!(!reachable(Bearing::default(), target, fewer, limit, policy), "{:?} {:?}", target, policy);
+                }
+            }
+        }
+
+        // Aims beyond a `u32` don't get split up, they're reached by
+        // backing up first and going further at a smaller one
+        assert!(plan_bearing(Position(1, 1 << 40), Objective::MinLength, Policy::Signed).unwrap().len() <= 5);
+        assert!(plan_bearing(Position(1, i64::MAX), Objective::MinLength, Policy::Signed).unwrap().len() <= 5);
+        assert!(plan_bearing(Position(-3, i64::MIN), Objective::MinLength, Policy::Signed).unwrap().len() <= 5);
+    }
+
+    #[test]
+    fn smallest_total() {
+        for policy in [Policy::Saturating, Policy::Checked, Policy::Signed] {
+            let totals = smallest_totals(30, policy);
+
+            for h in -6..=6_i64 {
+                for d in -20..=20_i64 {
+                    let target = Position(h, d);
+                    match plan_bearing(target, Objective::MinTotalDelta, policy) {
+                        Ok(plan) => assert_eq!(Some(&total(&plan)), totals.get(&(h, d)), "{:?} {:?}", target, policy),
+                        Err(_) => assert_eq!(totals.get(&(h, d)), None, "{:?} {:?}", target, policy),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn limits() {
+        assert!(plan_bearing(Position(-3, 6), Objective::MinLength, Policy::Saturating).is_err());
+        assert!(plan_bearing(Position(1, u32::MAX as i64 + 1), Objective::MinLength, Policy::Checked).is_err());
+        assert!(plan_bearing(Position(1, u32::MAX as i64), Objective::MinTotalDelta, Policy::Checked).is_ok());
+        assert!(plan_bearing(Position(-1, i64::MIN), Objective::MinTotalDelta, Policy::Signed).is_ok());
+
+        let far = Position(u32::MAX as i64 * 2 + 1, 0);
+        assert_eq!(plan_position(far, Policy::Signed).unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,66 @@
+/// How submarine arithmetic deals with results that don't fit.
+///
+/// All but `Signed` keep positions and aim within the range of a `u32`,
+/// as in the puzzle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Policy {
+    /// Stop at the ends of the range, so the submarine can't fly above
+    /// the surface. This is the default, and what the puzzle expects.
+    Saturating,
+    /// Fail with an error rather than leave the range
+    Checked,
+    /// Wrap around from one end of the range to the other
+    Wrapping,
+    /// Allow anything that fits in an `i64`, so the submarine can rise
+    /// above the surface and back up past where it started. Fails with an
+    /// error beyond that.
+    Signed,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self::Saturating
+    }
+}
+
+impl Policy {
+    /// Bring the exact result of a calculation into range, or `None` if
+    /// the policy says it's an error
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aoc2021::day2::Policy;
+    /// assert_eq!(Policy::Saturating.apply(-3), Some(0));
+    /// assert_eq!(Policy::Checked.apply(-3), None);
+    /// assert_eq!(Policy::Wrapping.apply(-3), Some(u32::MAX as i64 - 2));
+    /// assert_eq!(Policy::Signed.apply(-3), Some(-3));
+    /// ```
+    pub fn apply(self, value: i128) -> Option<i64> {
+        const MAX: i128 = u32::MAX as i128;
+
+        match self {
+            Self::Saturating => Some(value.clamp(0, MAX) as i64),
+            Self::Checked => (0..=MAX).contains(&value).then(|| value as i64),
+            Self::Wrapping => Some(value.rem_euclid(MAX + 1) as i64),
+            Self::Signed => i64::try_from(value).ok(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits() {
+        let big = u32::MAX as i128 + 5;
+
+        assert_eq!(Policy::Saturating.apply(big), Some(u32::MAX as i64));
+        assert_eq!(Policy::Checked.apply(big), None);
+        assert_eq!(Policy::Checked.apply(u32::MAX as i128), Some(u32::MAX as i64));
+        assert_eq!(Policy::Wrapping.apply(big), Some(4));
+        assert_eq!(Policy::Signed.apply(big), Some(big as i64));
+        assert_eq!(Policy::Signed.apply(i64::MIN as i128 - 1), None);
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use crate::error::column_of;
+use crate::{Error, Result};
+use super::{parse_delta, Command};
+
+/// Where something is in a script's source. The offset and length are in
+/// bytes, and the line and column are 1-based.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub offset: usize,
+    pub len: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    /// A parse error pointing at the start of this span
+    pub fn error(&self, message: impl Into<String>) -> Error {
+        Error::parse(message).at_column(self.column).at_line(self.line)
+    }
+
+    /// The span running from the start of this one to the end of `other`
+    fn to(self, other: Span) -> Self {
+        Self { len: other.offset + other.len - self.offset, ..self }
+    }
+}
+
+/// A single statement in a script, along with its label if it has one
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Statement {
+    pub label: Option<String>,
+    pub kind: StatementKind,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StatementKind {
+    Command(Command),
+    /// Runs the statements in the body `count` times over
+    Repeat { count: u32, body: Vec<Statement> },
+}
+
+/// A parsed submarine script.
+///
+/// Scripts are made of commands (`forward`, `back`, `down`, `up`, and
+/// `left` and `right` to turn by some degrees, each with a delta that can
+/// be negative to turn it around) and repeat blocks like
+/// `repeat 3 { forward 2 down 1 }`, which can be nested.
+/// Any statement can be labelled by starting it with `name:`, and
+/// anything after a `#` is a comment. Line breaks are just whitespace,
+/// so a line can hold as many statements as it likes, and the plain
+/// one-command-per-line puzzle input is a script too.
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day2::{Command, Script};
+/// let script: Script = "
+///     dive: repeat 2 {  # then level out
+///         down 3
+///         forward 1
+///     }
+///     level: down -6 forward +2
+/// ".parse()?;
+///
+/// let commands: Vec<Command> = script.commands().collect();
+/// assert_eq!(commands, [
+///     Command::Down(3), Command::Forward(1),
+///     Command::Down(3), Command::Forward(1),
+///     Command::Up(6), Command::Forward(2),
+/// ]);
+/// assert_eq!(script.labelled("level").unwrap().span.line, 6);
+///
+/// let error = "repeat 2 { forward 1 ".parse::<Script>().unwrap_err();
+/// assert_eq!(error.to_string(), "line 1, column 10: This `{` is never closed");
+/// # Ok::<(), aoc2021::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Script {
+    pub statements: Vec<Statement>,
+}
+
+impl Script {
+    /// Every command the script runs, in order. Repeat blocks are unrolled
+    /// as they're reached, so even huge repeat counts take no extra memory.
+    pub fn commands(&self) -> impl Iterator<Item = Command> + '_ {
+        unroll(&self.statements)
+    }
+
+    /// The statement with the given label, wherever it is in the script
+    pub fn labelled(&self, label: &str) -> Option<&Statement> {
+        fn find<'a>(statements: &'a [Statement], label: &str) -> Option<&'a Statement> {
+            statements.iter().find_map(|statement| match &statement.kind {
+                _ if statement.label.as_deref() == Some(label) => Some(statement),
+                StatementKind::Repeat { body, .. } => find(body, label),
+                StatementKind::Command(_) => None,
+            })
+        }
+
+        find(&self.statements, label)
+    }
+}
+
+fn unroll(statements: &[Statement]) -> Box<dyn Iterator<Item = Command> + '_> {
+    Box::new(statements.iter().flat_map(|statement| -> Box<dyn Iterator<Item = Command> + '_> {
+        match &statement.kind {
+            StatementKind::Command(command) => Box::new(std::iter::once(*command)),
+            StatementKind::Repeat { count, body } => Box::new((0..*count).flat_map(move |_| unroll(body))),
+        }
+    }))
+}
+
+impl FromStr for Script {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parser = Parser { tokens: tokenize(s), next: 0, labels: HashMap::new() };
+        let (statements, _) = parser.block(None)?;
+
+        Ok(Self { statements })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Token<'a> {
+    text: &'a str,
+    span: Span,
+}
+
+/// Split a script into words, numbers and punctuation, leaving out
+/// whitespace and comments
+fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+
+    for (n, line) in source.lines().enumerate() {
+        let line_offset = column_of(source, line) - 1;
+        let mut rest = line.split('#').next().unwrap_or_default();
+
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                break;
+            }
+
+            let len = if rest.starts_with(['{', '}', ':']) {
+                1
+            } else {
+                rest.find(|c: char| c.is_whitespace() || "{}:".contains(c)).unwrap_or(rest.len())
+            };
+
+            let (text, remainder) = rest.split_at(len);
+            let column = column_of(line, text);
+            tokens.push(Token { text, span: Span { offset: line_offset + column - 1, len, line: n + 1, column } });
+            rest = remainder;
+        }
+    }
+
+    tokens
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    next: usize,
+    labels: HashMap<String, Span>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.next).copied()
+    }
+
+    fn bump(&mut self) -> Option<Token<'a>> {
+        let token = self.peek();
+        if token.is_some() {
+            self.next += 1;
+        }
+
+        token
+    }
+
+    /// The next token, which has to be there. If it isn't, the error
+    /// points at the token before it.
+    fn expect(&mut self, after: Token, message: &str) -> Result<Token<'a>> {
+        self.bump().ok_or_else(|| after.span.error(message))
+    }
+
+    /// Statements up to the end of the script, or up to the `}` closing
+    /// the block opened at `open`, along with the closing `}`
+    fn block(&mut self, open: Option<Token>) -> Result<(Vec<Statement>, Option<Token<'a>>)> {
+        let mut statements = vec![];
+
+        loop {
+            match (self.peek(), open) {
+                (None, None) => return Ok((statements, None)),
+                (None, Some(open)) => return Err(open.span.error("This `{` is never closed")),
+                (Some(close), None) if close.text == "}" => {
+                    return Err(close.span.error("There's no `{` for this `}` to close"))
+                }
+                (Some(close), Some(_)) if close.text == "}" => {
+                    self.next += 1;
+                    return Ok((statements, Some(close)));
+                }
+                (Some(first), _) => {
+                    self.next += 1;
+                    statements.push(self.statement(first)?);
+                }
+            }
+        }
+    }
+
+    /// The statement starting with the token `first`
+    fn statement(&mut self, first: Token) -> Result<Statement> {
+        let (label, word) = match self.peek() {
+            Some(colon) if colon.text == ":" => {
+                self.next += 1;
+                let label = self.label(first)?;
+                let word = self.expect(colon, &format!("Label `{}` isn't followed by anything to label", label))?;
+                (Some(label), word)
+            }
+            _ => (None, first),
+        };
+
+        let (kind, last) = match word.text {
+            "repeat" => {
+                let count = self.expect(word, "`repeat` needs a count")?;
+                let times = count
+                    .text
+                    .parse()
+                    .ok()
+                    .filter(|&times: &u32| times > 0)
+                    .ok_or_else(|| count.span.error(format!("Repeat counts are positive integers, not {}", count.text)))?;
+
+                let open = self.expect(count, "Expected `{` after the repeat count")?;
+                if open.text != "{" {
+                    return Err(open.span.error(format!("Expected `{{` after the repeat count, not {}", open.text)));
+                }
+
+                let (body, close) = self.block(Some(open))?;
+                (StatementKind::Repeat { count: times, body }, close.unwrap_or(open))
+            }
+            "{" | "}" | ":" => return Err(word.span.error(format!("Unexpected `{}`", word.text))),
+            name => {
+                let delta = self.expect(word, &format!("`{}` needs a delta", name))?;
+                let size = parse_delta(delta.text).map_err(|e| e.at_column(delta.span.column).at_line(delta.span.line))?;
+                let command = Command::signed(name, size)
+                    .ok_or_else(|| word.span.error(format!("Not sure what to make of command: {}", name)))?;
+
+                (StatementKind::Command(command), delta)
+            }
+        };
+
+        Ok(Statement { label, kind, span: first.span.to(last.span) })
+    }
+
+    /// Check a label is a usable name, and hasn't been used before
+    fn label(&mut self, token: Token) -> Result<String> {
+        let valid = token.text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && token.text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(token.span.error(format!("Labels are names like `dive` or `leg_2`, not {}", token.text)));
+        }
+
+        if let Some(previous) = self.labels.insert(token.text.to_string(), token.span) {
+            return Err(token.span.error(format!("Label `{}` is already used on line {}", token.text, previous.line)));
+        }
+
+        Ok(token.text.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(script: &str) -> String {
+        script.parse::<Script>().unwrap_err().to_string()
+    }
+
+    #[test]
+    fn spans() {
+        let script: Script = "forward 5\n  top: repeat 2 {\n    down 1 # sink\n  }\nup 2".parse().unwrap();
+        let top = script.labelled("top").unwrap();
+
+        assert_eq!(top.span, Span { offset: 12, len: 37, line: 2, column: 3 });
+        assert_eq!(script.statements[2].span, Span { offset: 50, len: 4, line: 5, column: 1 });
+        assert_eq!(script.commands().count(), 4);
+    }
+
+    #[test]
+    fn diagnostics() {
+        assert_eq!(error("forward 1\nsideways 2"), "line 2, column 1: Not sure what to make of command: sideways");
+        assert_eq!(error("forward 1\ndown x"), "line 2, column 6: Command delta wasn't an integer: x");
+        assert_eq!(error("down 99999999999"), "line 1, column 6: Command delta is too big: 99999999999");
+        assert_eq!(error("forward"), "line 1, column 1: `forward` needs a delta");
+        assert_eq!(error("repeat -1 { up 1 }"), "line 1, column 8: Repeat counts are positive integers, not -1");
+        assert_eq!(error("up 1\n  repeat 0 { up 1 }"), "line 2, column 10: Repeat counts are positive integers, not 0");
+        assert_eq!(error("repeat 2 up 1"), "line 1, column 10: Expected `{` after the repeat count, not up");
+        assert_eq!(error("up 1 }"), "line 1, column 6: There's no `{` for this `}` to close");
+        assert_eq!(error("a: up 1\na: down 1"), "line 2, column 1: Label `a` is already used on line 1");
+        assert_eq!(error("2a: up 1"), "line 1, column 1: Labels are names like `dive` or `leg_2`, not 2a");
+        assert_eq!(error("up 1 end:"), "line 1, column 9: Label `end` isn't followed by anything to label");
+    }
+}
@@ -0,0 +1,223 @@
+use crate::Result;
+use super::{Bearing, Command, Policy, Position, State};
+
+/// A movement model: a way of interpreting commands to steer a submarine
+pub trait Submarine: State {
+    /// Execute a single command with the given numeric policy, failing if
+    /// the policy doesn't allow where the command would take us
+    fn try_execute(self, cmd: Command, policy: Policy) -> Result<Self>;
+
+    /// Execute a single command, saturating at the ends of the range
+    fn execute(self, cmd: Command) -> Self {
+        self.try_execute(cmd, Policy::Saturating).expect("Saturating arithmetic can't fail")
+    }
+}
+
+impl Submarine for Position {
+    fn try_execute(self, cmd: Command, policy: Policy) -> Result<Self> {
+        Position::try_execute(self, cmd, policy)
+    }
+}
+
+impl Submarine for Bearing {
+    fn try_execute(self, cmd: Command, policy: Policy) -> Result<Self> {
+        Bearing::try_execute(self, cmd, policy)
+    }
+}
+
+/// Steer a submarine through every command in turn, with any movement
+/// model
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day2::{navigate, Bearing, Position, Script};
+/// let script: Script = "forward 5 down 5 forward 8 up 3 down 8 forward 2".parse()?;
+///
+/// assert_eq!(navigate(Position::default(), script.commands()), Position(15, 10));
+/// assert_eq!(navigate(Bearing::default(), script.commands()).position, Position(15, 60));
+/// # Ok::<(), aoc2021::Error>(())
+/// ```
+pub fn navigate<S, I>(start: S, commands: I) -> S where S: Submarine, I: IntoIterator<Item = Command> {
+    commands.into_iter().fold(start, S::execute)
+}
+
+/// Steer a submarine through every command in turn with the given numeric
+/// policy, stopping at the first command that fails
+pub fn try_navigate<S, I>(start: S, commands: I, policy: Policy) -> Result<S>
+where
+    S: Submarine,
+    I: IntoIterator<Item = Command>,
+{
+    commands.into_iter().try_fold(start, |acc, cmd| acc.try_execute(cmd, policy))
+}
+
+/// Like `Bearing`, but the submarine levels out as it goes. After each
+/// move forward or back, the aim eases toward zero by `drag` for every
+/// unit travelled, stopping once level.
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day2::{navigate, Command, DragBearing, Position};
+/// let commands = [Command::Down(5), Command::Forward(2), Command::Forward(2)];
+/// let sub = navigate(DragBearing::new(1), commands);
+///
+/// assert_eq!(sub.bearing.position, Position(4, 16));
+/// assert_eq!(sub.bearing.aim, 1);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DragBearing {
+    pub bearing: Bearing,
+    pub drag: u32,
+}
+
+impl DragBearing {
+    /// A submarine at the surface, with no aim and the given drag
+    pub fn new(drag: u32) -> Self {
+        Self { bearing: Bearing::default(), drag }
+    }
+}
+
+impl State for DragBearing {
+    const COLUMNS: &'static str = "horizontal,depth,aim";
+
+    fn position(&self) -> Position {
+        self.bearing.position
+    }
+
+    fn csv(&self) -> String {
+        self.bearing.csv()
+    }
+}
+
+impl Submarine for DragBearing {
+    fn try_execute(self, cmd: Command, policy: Policy) -> Result<Self> {
+        let mut bearing = self.bearing.try_execute(cmd, policy)?;
+
+        if let Command::Forward(x) | Command::Back(x) = cmd {
+            let slowdown = (self.drag as i128 * x as i128).min(bearing.aim.unsigned_abs() as i128) as i64;
+            bearing.aim -= slowdown * bearing.aim.signum();
+        }
+
+        Ok(Self { bearing, ..self })
+    }
+}
+
+/// Like `Bearing`, but out in three dimensions. `left` and `right` turn
+/// the submarine, with its yaw in degrees anticlockwise from east, and
+/// moving forward or back takes it east and north along that heading.
+/// Its `bearing` goes on counting how far it's gone forward, whichever
+/// way it was facing, and how deep.
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day2::{navigate, Bearing3d, Command, Position};
+/// let commands = [Command::Forward(4), Command::Left(90), Command::Down(1), Command::Forward(6)];
+/// let sub = navigate(Bearing3d::default(), commands);
+///
+/// assert_eq!(sub.bearing.position, Position(10, 6));
+/// assert_eq!((sub.yaw, sub.east, sub.north), (90, 4.0, 6.0));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Bearing3d {
+    pub bearing: Bearing,
+    /// Degrees anticlockwise from east, from 0 up to 360
+    pub yaw: i64,
+    pub east: f64,
+    pub north: f64,
+}
+
+impl Bearing3d {
+    /// How far east and north one step forward takes the submarine. Right
+    /// angles are exact.
+    fn heading(&self) -> (f64, f64) {
+        match self.yaw {
+            0 => (1.0, 0.0),
+            90 => (0.0, 1.0),
+            180 => (-1.0, 0.0),
+            270 => (0.0, -1.0),
+            yaw => {
+                let angle = (yaw as f64).to_radians();
+                (angle.cos(), angle.sin())
+            }
+        }
+    }
+}
+
+impl State for Bearing3d {
+    const COLUMNS: &'static str = "horizontal,depth,aim,yaw,east,north";
+
+    fn position(&self) -> Position {
+        self.bearing.position
+    }
+
+    fn csv(&self) -> String {
+        format!("{},{},{},{}", self.bearing.csv(), self.yaw, self.east, self.north)
+    }
+}
+
+impl Submarine for Bearing3d {
+    /// Turns always succeed, going round past 360 degrees either way.
+    /// East and north follow however far the numeric policy lets the
+    /// submarine go forward.
+    fn try_execute(self, cmd: Command, policy: Policy) -> Result<Self> {
+        let bearing = self.bearing.try_execute(cmd, policy)?;
+        let moved = (bearing.position.0 - self.bearing.position.0) as f64;
+        let (east, north) = self.heading();
+
+        Ok(Self {
+            bearing,
+            yaw: (self.yaw + cmd.turn()).rem_euclid(360),
+            east: self.east + moved * east,
+            north: self.north + moved * north,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drag() {
+        // Drag never flips the aim over
+        let sub = navigate(DragBearing::new(10), [Command::Down(3), Command::Forward(2)]);
+        assert_eq!(sub.bearing, Bearing { position: Position(2, 6), aim: 0 });
+
+        let sub = try_navigate(DragBearing::new(1), [Command::Up(3), Command::Forward(2)], Policy::Signed).unwrap();
+        assert_eq!(sub.bearing, Bearing { position: Position(2, -6), aim: -1 });
+
+        // Without drag, it's the same as a plain bearing
+        let commands = [Command::Down(3), Command::Forward(2), Command::Up(1), Command::Back(1)];
+        assert_eq!(navigate(DragBearing::new(0), commands).bearing, navigate(Bearing::default(), commands));
+    }
+
+    #[test]
+    fn yaw() {
+        let script: crate::day2::Script = "left 450 forward 3 right -270 back 2 right 45 down 1 forward 2".parse().unwrap();
+        let sub = navigate(Bearing3d::default(), script.commands());
+
+        // Turning goes round either way, and doesn't move the submarine
+        assert_eq!(sub.yaw, 315);
+        assert_eq!(sub.bearing.position, Position(3, 2));
+        assert_eq!(navigate(Bearing::default(), script.commands()), sub.bearing);
+        assert!((sub.east - (2.0_f64.sqrt() - 2.0)).abs() < 1e-9);
+        assert!((sub.north - (3.0 - 2.0_f64.sqrt())).abs() < 1e-9);
+
+        // Only as far as the policy lets it go counts
+        let sub = navigate(Bearing3d::default(), [Command::Right(90), Command::Back(5)]);
+        assert_eq!((sub.yaw, sub.east, sub.north), (270, 0.0, 0.0));
+    }
+
+    #[test]
+    fn overflow() {
+        let commands = [Command::Up(3), Command::Forward(2)];
+
+        assert!(try_navigate(Position::default(), commands, Policy::Checked).is_err());
+        assert!(try_navigate(DragBearing::new(1), commands, Policy::Checked).is_err());
+        assert!(try_navigate(Bearing3d::default(), commands, Policy::Checked).is_err());
+        assert_eq!(try_navigate(Position::default(), commands, Policy::Signed).unwrap(), Position(2, -3));
+    }
+}
@@ -0,0 +1,192 @@
+use std::fmt::Write;
+use super::{Bearing, Command, Position};
+
+/// The state of a movement model, as recorded along a trajectory
+pub trait State: Copy {
+    /// The CSV column names for the fields `csv` writes out
+    const COLUMNS: &'static str;
+
+    /// Where the submarine is
+    fn position(&self) -> Position;
+
+    /// The state's fields, as a CSV fragment matching `COLUMNS`
+    fn csv(&self) -> String;
+}
+
+impl State for Position {
+    const COLUMNS: &'static str = "horizontal,depth";
+
+    fn position(&self) -> Position {
+        *self
+    }
+
+    fn csv(&self) -> String {
+        format!("{},{}", self.0, self.1)
+    }
+}
+
+impl State for Bearing {
+    const COLUMNS: &'static str = "horizontal,depth,aim";
+
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn csv(&self) -> String {
+        format!("{},{},{}", self.position.0, self.position.1, self.aim)
+    }
+}
+
+/// A command along with the state it left the submarine in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Step<S> {
+    pub command: Command,
+    pub state: S,
+}
+
+/// Every state a submarine passed through on its way through a list of
+/// commands, rather than just where it ended up.
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day2::{Bearing, Position, Script, Trajectory};
+/// let script: Script = "forward 5 down 5 forward 8 up 3 down 8 forward 2".parse()?;
+/// let trajectory = Trajectory::record(Bearing::default(), script.commands(), Bearing::execute);
+///
+/// assert_eq!(trajectory.end().position, Position(15, 60));
+/// assert_eq!(trajectory.max_depth(), 60);
+/// assert_eq!(trajectory.depth_crossed(30), Some(3));
+/// assert!(trajectory.csv().starts_with("step,command,delta,horizontal,depth,aim\n0,,,0,0,0\n1,forward,5,5,0,0\n"));
+/// # Ok::<(), aoc2021::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trajectory<S> {
+    start: S,
+    steps: Vec<Step<S>>,
+}
+
+impl<S> Trajectory<S> where S: State {
+    /// Run each command in turn from the starting state, keeping every
+    /// state along the way
+    pub fn record<I, F>(start: S, commands: I, execute: F) -> Self
+    where
+        I: IntoIterator<Item = Command>,
+        F: Fn(S, Command) -> S,
+    {
+        let mut state = start;
+        let steps = commands
+            .into_iter()
+            .map(|command| {
+                state = execute(state, command);
+                Step { command, state }
+            })
+            .collect();
+
+        Self { start, steps }
+    }
+
+    pub fn start(&self) -> &S {
+        &self.start
+    }
+
+    pub fn steps(&self) -> &[Step<S>] {
+        &self.steps
+    }
+
+    /// Where the submarine ended up
+    pub fn end(&self) -> &S {
+        self.steps.last().map_or(&self.start, |x| &x.state)
+    }
+
+    /// Every state, starting with the one before any commands were run
+    pub fn states(&self) -> impl Iterator<Item = &S> + '_ {
+        std::iter::once(&self.start).chain(self.steps.iter().map(|x| &x.state))
+    }
+
+    /// The deepest the submarine got at any point
+    pub fn max_depth(&self) -> i64 {
+        self.states().map(|x| x.position().1).max().unwrap_or_default()
+    }
+
+    /// How far the submarine travelled, following the straight line
+    /// between each state and the next
+    pub fn distance(&self) -> f64 {
+        self.states()
+            .zip(self.states().skip(1))
+            .map(|(a, b)| {
+                let (a, b) = (a.position(), b.position());
+                let dx = a.0 as f64 - b.0 as f64;
+                let dy = a.1 as f64 - b.1 as f64;
+                dx.hypot(dy)
+            })
+            .sum()
+    }
+
+    /// The number of the first step that took the submarine from above the
+    /// given depth to at or below it. Steps are numbered as in `csv`, from
+    /// 1 for the first command, so this is `steps()[n - 1]`.
+    pub fn depth_crossed(&self, threshold: i64) -> Option<usize> {
+        self.states()
+            .zip(self.steps.iter())
+            .position(|(before, after)| before.position().1 < threshold && after.state.position().1 >= threshold)
+            .map(|idx| idx + 1)
+    }
+
+    /// The whole path as CSV, with a header row. The starting state is
+    /// step 0, with no command.
+    pub fn csv(&self) -> String {
+        let mut csv = format!("step,command,delta,{}\n", S::COLUMNS);
+        let _ = writeln!(csv, "0,,,{}", self.start.csv());
+
+        for (n, step) in self.steps.iter().enumerate() {
+            let _ = writeln!(csv, "{},{},{},{}", n + 1, step.command.name(), step.command.delta(), step.state.csv());
+        }
+
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions() {
+        let commands = [Command::Down(3), Command::Forward(4), Command::Up(3), Command::Down(1)];
+        let trajectory = Trajectory::record(Position::default(), commands, Position::execute);
+
+        assert_eq!(trajectory.steps().len(), 4);
+        assert_eq!(trajectory.end(), &Position(4, 1));
+        assert_eq!(trajectory.max_depth(), 3);
+        assert_eq!(trajectory.distance(), 11.0);
+        assert_eq!(trajectory.depth_crossed(1), Some(1));
+        assert_eq!(trajectory.depth_crossed(4), None);
+        assert_eq!(
+            trajectory.csv(),
+            "step,command,delta,horizontal,depth\n0,,,0,0\n1,down,3,0,3\n2,forward,4,4,3\n3,up,3,4,0\n4,down,1,4,1\n"
+        );
+    }
+
+    #[test]
+    fn crossing_matches_csv() {
+        let commands = [Command::Forward(2), Command::Down(2), Command::Forward(3), Command::Up(1), Command::Forward(4)];
+        let trajectory = Trajectory::record(Bearing::default(), commands, Bearing::execute);
+        let csv = trajectory.csv();
+
+        let step = trajectory.depth_crossed(10).unwrap();
+        let row: Vec<&str> = csv.lines().nth(step + 1).unwrap().split(',').collect();
+        assert_eq!(row[..3], [step.to_string().as_str(), "forward", "4"]);
+        assert_eq!(row[4], "10");
+        assert_eq!(trajectory.steps()[step - 1].state.position, Position(9, 10));
+    }
+
+    #[test]
+    fn empty() {
+        let trajectory = Trajectory::record(Bearing::default(), vec![], Bearing::execute);
+
+        assert_eq!(trajectory.end(), &Bearing::default());
+        assert_eq!(trajectory.max_depth(), 0);
+        assert_eq!(trajectory.distance(), 0.0);
+    }
+}
@@ -1,3 +1,11 @@
+use crate::{Error, Result, Solution};
+
+pub mod bits;
+pub mod packed;
+
+pub use bits::Bits;
+pub use packed::PackedDiagnostics;
+
 pub struct PowerConsumption {
     registers: Vec<u32>,
     diag_count: u32,
@@ -17,8 +25,26 @@ impl PowerConsumption {
         }
     }
 
-    pub fn add_diagnostic(&mut self, input: &str) {
-        // Dynamically resize the set of collectors
+    /// Count the bits of another diagnostic. It has to be made of `0`s and
+    /// `1`s, and be as wide as the ones before it. Nothing is counted if
+    /// it isn't, and the error has the column of the problem, leaving the
+    /// line to the caller.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aoc2021::day3::PowerConsumption;
+    /// let mut pc = PowerConsumption::new();
+    /// pc.add_diagnostic("00100")?;
+    ///
+    /// assert_eq!(pc.add_diagnostic("0010").unwrap_err().to_string(), "Expected a diagnostic 5 bits wide, not 4");
+    /// assert_eq!(pc.add_diagnostic("00120").unwrap_err().to_string(), "column 4: Not a binary digit: 2");
+    /// # Ok::<(), aoc2021::Error>(())
+    /// ```
+    pub fn add_diagnostic(&mut self, input: &str) -> Result<()> {
+        let width = (self.diag_count > 0).then(|| self.registers.len());
+        check_diagnostic(input, width)?;
+
         self.registers.resize(input.len(), 0);
 
         // Process each index in turn and count the set "bits"
@@ -29,9 +55,24 @@ impl PowerConsumption {
             .for_each(|(idx, _)| self.registers[idx] += 1);
 
         self.diag_count += 1;
+        Ok(())
     }
 
-    /// Calculate the gamma rate of the sampled diagnostics
+    /// The most common bit in each position of the sampled diagnostics,
+    /// however wide they are
+    pub fn gamma(&self) -> Bits {
+        let pivot = self.diag_count / 2;
+
+        self.registers.iter().map(|&x| x > pivot).collect()
+    }
+
+    /// The least common bit in each position of the sampled diagnostics
+    pub fn epsilon(&self) -> Bits {
+        self.gamma().complement()
+    }
+
+    /// Calculate the gamma rate of the sampled diagnostics, which fails if
+    /// the diagnostics are too wide for it to fit
     ///
     /// # Examples
     ///
@@ -42,23 +83,18 @@ impl PowerConsumption {
     ///                    "00111", "11100", "10000", "11001", "00010", "01010"];
     ///
     /// for s in samples {
-    ///     pc.add_diagnostic(s);
+    ///     pc.add_diagnostic(s)?;
     /// }
     ///
-    /// assert_eq!(pc.gamma_rate(), 22);
+    /// assert_eq!(pc.gamma_rate()?, 22);
+    /// # Ok::<(), aoc2021::Error>(())
     /// ```
-    pub fn gamma_rate(&self) -> u32 {
-        let pivot = self.diag_count / 2;
-
-        self.registers
-            .iter()
-            .rev()
-            .enumerate()
-            .filter(|&(_, val)| val > &pivot)
-            .fold(0, |acc, x| acc | (1 << x.0))
+    pub fn gamma_rate(&self) -> Result<u64> {
+        u64::try_from(&self.gamma())
     }
 
-    /// Calculate the epsilon rate of the sampled diagnostics
+    /// Calculate the epsilon rate of the sampled diagnostics, which fails
+    /// if the diagnostics are too wide for it to fit
     ///
     /// # Examples
     ///
@@ -69,21 +105,67 @@ impl PowerConsumption {
     ///                    "00111", "11100", "10000", "11001", "00010", "01010"];
     ///
     /// for s in samples {
-    ///     pc.add_diagnostic(s);
+    ///     pc.add_diagnostic(s)?;
     /// }
     ///
-    /// assert_eq!(pc.epsilon_rate(), 9);
+    /// assert_eq!(pc.epsilon_rate()?, 9);
+    /// # Ok::<(), aoc2021::Error>(())
     /// ```
-    pub fn epsilon_rate(&self) -> u32 {
-        // Epsilon is just the bitwise complement of gamma.
-        // Simply taking the complement isn't sufficient, due to
-        // extension
-        !self.gamma_rate() & ((1 << self.registers.len()) - 1)
+    pub fn epsilon_rate(&self) -> Result<u64> {
+        u64::try_from(&self.epsilon())
+    }
+}
+
+/// Check that a diagnostic is made of `0`s and `1`s, and has the expected
+/// width if there is one
+fn check_diagnostic(sample: &str, width: Option<usize>) -> Result<()> {
+    if sample.is_empty() {
+        return Err(Error::parse("Diagnostics need at least one bit"));
     }
+
+    if let Some(bad) = sample.find(|c| c != '0' && c != '1') {
+        let c = sample[bad..].chars().next().unwrap_or_default();
+        return Err(Error::parse(format!("Not a binary digit: {}", c)).at_column(bad + 1));
+    }
+
+    match width {
+        Some(width) if width != sample.len() => {
+            Err(Error::parse(format!("Expected a diagnostic {} bits wide, not {}", width, sample.len())))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Check that every sample is a binary number of the same width as the
+/// first, returning that width. Errors carry the line number of the bad
+/// sample, counting the first as line 1.
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day3::check_diagnostics;
+/// assert_eq!(check_diagnostics(&["00100", "11110"])?, 5);
+///
+/// let error = check_diagnostics(&["00100", "11110", "1011"]).unwrap_err();
+/// assert_eq!(error.to_string(), "line 3: Expected a diagnostic 5 bits wide, not 4");
+/// assert!(check_diagnostics(&[]).is_err());
+/// # Ok::<(), aoc2021::Error>(())
+/// ```
+pub fn check_diagnostics(samples: &[&str]) -> Result<usize> {
+    let width = samples
+        .first()
+        .ok_or_else(|| Error::NoAnswer("No diagnostics to rate".into()))?
+        .len();
+
+    for (n, sample) in samples.iter().enumerate() {
+        check_diagnostic(sample, Some(width)).map_err(|e| e.at_line(n + 1))?;
+    }
+
+    Ok(width)
 }
 
 /// Rate the life support system, producing the O2 generator rating and the
-/// CO2 scrubber rating
+/// CO2 scrubber rating. Fails if the ratings are too wide to fit.
 ///
 /// # Examples
 ///
@@ -92,31 +174,44 @@ impl PowerConsumption {
 /// let mut samples = vec!["00100", "11110", "10110", "10111", "10101", "01111",
 ///                    "00111", "11100", "10000", "11001", "00010", "01010"];
 ///
-/// assert_eq!(rate_life_support(&mut samples), (23, 10));
-pub fn rate_life_support(samples: &mut [&str]) -> (u32, u32) {
-    // Making the assumption that all of the samples are the same length
-    // and that there's at least one of em :)
-    let bit_count = samples[0].len();
-
-    // Partition the data set based on the first bit. The partition with
-    // the more frequent bit is the O2 reading, and the other is the CO2
-    // reading
-    let (mut o2_samples, mut co2_samples) = partition_by_bit(samples, 0);
-
-    for n in 1..bit_count {
-        if o2_samples.len() > 1 {
-            o2_samples = partition_by_bit(o2_samples, n).0;
-        }
+/// assert_eq!(rate_life_support(&mut samples)?, (23, 10));
+/// # Ok::<(), aoc2021::Error>(())
+/// ```
+pub fn rate_life_support(samples: &mut [&str]) -> Result<(u64, u64)> {
+    let (o2, co2) = life_support_ratings(samples)?;
 
-        if co2_samples.len() > 1 {
-            co2_samples = partition_by_bit(co2_samples, n).1;
+    Ok((u64::try_from(&o2)?, u64::try_from(&co2)?))
+}
+
+/// The O2 generator and CO2 scrubber ratings, however wide they are. The
+/// samples are checked as by `check_diagnostics` first, so there has to
+/// be at least one, and they all have to be the same width.
+pub fn life_support_ratings(samples: &mut [&str]) -> Result<(Bits, Bits)> {
+    let bit_count = check_diagnostics(samples)?;
+    let o2 = rating(samples, bit_count, true);
+    let co2 = rating(samples, bit_count, false);
+
+    Ok((o2.parse()?, co2.parse()?))
+}
+
+/// Narrow the samples down a bit at a time, keeping the ones with the more
+/// common bit, or the less common one, until there's only one left. The
+/// less common bit might not be there at all, when the samples left agree
+/// on it, and then they're all kept.
+fn rating<'a>(mut samples: &mut [&'a str], bit_count: usize, most_common: bool) -> &'a str {
+    for n in 0..bit_count {
+        if samples.len() <= 1 {
+            break;
         }
+
+        samples = match partition_by_bit(samples, n) {
+            (more, _) if most_common => more,
+            (more, []) => more,
+            (_, fewer) => fewer,
+        };
     }
 
-    // When all is said and done there should be a single sample left in each one
-    let o2_rating = u32::from_str_radix(o2_samples[0], 2).unwrap_or(0);
-    let co2_rating = u32::from_str_radix(co2_samples[0], 2).unwrap_or(0);
-    (o2_rating, co2_rating)
+    samples[0]
 }
 
 /// Partitions the incoming samples based on the frequency of the bits in the
@@ -124,8 +219,24 @@ pub fn rate_life_support(samples: &mut [&str]) -> (u32, u32) {
 /// first position, and the less frequent sample in the second position. If both
 /// occur with equal frequency, then the first element contains the samples with
 /// a 1 bit.
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day3::partition_by_bit;
+/// let mut samples = vec!["00100", "11110", "10110", "01111"];
+///
+/// let (more, fewer) = partition_by_bit(&mut samples, 0);
+/// assert_eq!(more.len(), 2);
+/// assert_eq!(fewer.len(), 2);
+/// assert!(more.iter().all(|x| x.starts_with('1')));
+///
+/// let (more, fewer) = partition_by_bit(&mut samples, 2);
+/// assert_eq!(more.len(), 4);
+/// assert!(fewer.is_empty());
+/// ```
 pub fn partition_by_bit<'a, 'b>(samples: &'a mut[&'b str], which_bit: usize) -> (&'a mut [&'b str], &'a mut [&'b str]) {
-    let pivot = samples.iter_mut().partition_in_place(|x| x.chars().nth(which_bit) == Some('1'));
+    let pivot = partition_in_place(samples, |x| x.as_bytes().get(which_bit) == Some(&b'1'));
     let (ones, zeroes) = samples.split_at_mut(pivot);
 
     if ones.len() >= zeroes.len() {
@@ -135,25 +246,124 @@ pub fn partition_by_bit<'a, 'b>(samples: &'a mut[&'b str], which_bit: usize) ->
     }
 }
 
+/// Reorders the samples so that everything matching the predicate comes
+/// first, returning the number of matches. Order within each half isn't
+/// preserved.
+#[cfg(feature = "nightly")]
+fn partition_in_place(samples: &mut [&str], predicate: impl FnMut(&&str) -> bool) -> usize {
+    samples.iter_mut().partition_in_place(predicate)
+}
+
+/// Reorders the samples so that everything matching the predicate comes
+/// first, returning the number of matches. Order within each half isn't
+/// preserved.
+#[cfg(not(feature = "nightly"))]
+fn partition_in_place(samples: &mut [&str], mut predicate: impl FnMut(&&str) -> bool) -> usize {
+    let mut pivot = 0;
+    for idx in 0..samples.len() {
+        if predicate(&samples[idx]) {
+            samples.swap(pivot, idx);
+            pivot += 1;
+        }
+    }
+
+    pivot
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    type Input = PackedDiagnostics;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        input.parse()
+    }
+
+    fn part1(input: &Self::Input) -> Result<u64> {
+        product(input.gamma_rate()?, input.epsilon_rate()?)
+    }
+
+    fn part2(input: &Self::Input) -> Result<u64> {
+        let (o2, co2) = input.rate_life_support()?;
+
+        product(o2, co2)
+    }
+}
+
+fn product(a: u64, b: u64) -> Result<u64> {
+    a.checked_mul(b).ok_or_else(|| Error::Overflow(format!("{} * {} doesn't fit in a u64", a, b)))
+}
+
 #[cfg(test)]
 mod answers {
     use super::*;
+    use crate::inputs::{Inputs, Kind, DEFAULT_INPUT_DIR};
 
     #[test]
     fn puzzle1() {
         let mut pc = PowerConsumption::new();
-        for line in include_str!("./input/day3").lines() {
-            pc.add_diagnostic(line);
+        for line in Inputs::new(DEFAULT_INPUT_DIR).load(3, Kind::Puzzle).unwrap().lines() {
+            pc.add_diagnostic(line).unwrap();
         }
 
-        assert_eq!(pc.gamma_rate() * pc.epsilon_rate(), 2498354);
+        assert_eq!(pc.gamma_rate().unwrap() * pc.epsilon_rate().unwrap(), 2498354);
     }
 
     #[test]
     fn puzzle2() {
-        let mut data: Vec<&str> = include_str!("./input/day3").lines().collect();
-        let result = rate_life_support(&mut data);
+        let input = Inputs::new(DEFAULT_INPUT_DIR).load(3, Kind::Puzzle).unwrap();
+        let mut data: Vec<&str> = input.lines().collect();
+        let result = rate_life_support(&mut data).unwrap();
 
         assert_eq!(result.0 * result.1, 3277956);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_diagnostics() {
+        let samples = ["1".repeat(130) + "0", "1".repeat(131), "0".repeat(131)];
+        let mut pc = PowerConsumption::new();
+        for sample in &samples {
+            pc.add_diagnostic(sample).unwrap();
+        }
+
+        assert_eq!(pc.gamma().to_string(), "1".repeat(130) + "0");
+        assert_eq!(u128::try_from(&pc.epsilon()).unwrap(), 1);
+        assert!(pc.gamma_rate().is_err());
+        assert!(u128::try_from(&pc.gamma()).is_err());
+
+        let mut samples: Vec<&str> = samples.iter().map(String::as_str).collect();
+        let (o2, co2) = life_support_ratings(&mut samples).unwrap();
+        assert_eq!(o2.to_string(), "1".repeat(131));
+        assert_eq!(u128::try_from(&co2).unwrap(), 0);
+        assert!(rate_life_support(&mut samples).is_err());
+    }
+
+    #[test]
+    fn agreeing_bits() {
+        // Every sample has the same first bit, so there's no less common one
+        assert_eq!(rate_life_support(&mut ["110", "101", "100"]).unwrap(), (0b101, 0b110));
+        assert_eq!(rate_life_support(&mut ["011", "011"]).unwrap(), (0b011, 0b011));
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(matches!(rate_life_support(&mut []), Err(Error::NoAnswer(_))));
+
+        let error = rate_life_support(&mut ["0101", "1x01"]).unwrap_err();
+        assert_eq!(error.to_string(), "line 2, column 2: Not a binary digit: x");
+
+        let error = Day3::parse("0101\n\n1101\n110\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 4: Expected a diagnostic 4 bits wide, not 3");
+
+        // A bad diagnostic leaves the counts alone
+        let mut pc = PowerConsumption::new();
+        pc.add_diagnostic("011").unwrap();
+        assert!(pc.add_diagnostic("1111").is_err());
+        assert_eq!(pc.gamma().to_string(), "011");
+    }
+}
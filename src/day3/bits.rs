@@ -0,0 +1,119 @@
+use std::fmt;
+use std::str::FromStr;
+use crate::{Error, Result};
+
+/// A string of bits of any length, most significant first
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day3::Bits;
+/// let bits: Bits = "0010110".parse()?;
+///
+/// assert_eq!(bits.len(), 7);
+/// assert_eq!(bits.to_string(), "0010110");
+/// assert_eq!(bits.complement().to_string(), "1101001");
+/// assert_eq!(u8::try_from(&bits)?, 22);
+///
+/// let wide: Bits = "1".repeat(40).parse()?;
+/// assert!(u32::try_from(&wide).is_err());
+/// assert_eq!(u64::try_from(&wide)?, (1 << 40) - 1);
+/// # Ok::<(), aoc2021::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Bits {
+    bits: Vec<bool>,
+}
+
+impl Bits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    /// The bit at the given position, counting from the most significant
+    pub fn get(&self, idx: usize) -> Option<bool> {
+        self.bits.get(idx).copied()
+    }
+
+    /// Add a bit on the least significant end
+    pub fn push(&mut self, bit: bool) {
+        self.bits.push(bit);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        self.bits.iter().copied()
+    }
+
+    /// Every bit flipped, keeping the same width
+    pub fn complement(&self) -> Self {
+        self.iter().map(|x| !x).collect()
+    }
+
+    /// How many bits it takes to hold the value, ignoring leading zeroes
+    pub fn significant_bits(&self) -> usize {
+        self.bits.iter().position(|&x| x).map_or(0, |first| self.len() - first)
+    }
+}
+
+impl FromIterator<bool> for Bits {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        Self { bits: iter.into_iter().collect() }
+    }
+}
+
+impl FromStr for Bits {
+    type Err = Error;
+
+    /// Parses a string of `0`s and `1`s, reporting the column of anything
+    /// else
+    fn from_str(s: &str) -> Result<Self> {
+        s.chars()
+            .enumerate()
+            .map(|(idx, c)| match c {
+                '0' => Ok(false),
+                '1' => Ok(true),
+                other => Err(Error::parse(format!("Not a binary digit: {}", other)).at_column(idx + 1)),
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Bits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits: String = self.iter().map(|x| if x { '1' } else { '0' }).collect();
+        f.pad(&digits)
+    }
+}
+
+macro_rules! try_into_integer {
+    ($($int:ty),*) => {
+        $(
+            impl TryFrom<&Bits> for $int {
+                type Error = Error;
+
+                /// The bits' value, as long as it fits
+                fn try_from(bits: &Bits) -> Result<Self> {
+                    if bits.significant_bits() > <$int>::BITS as usize {
+                        return Err(Error::Overflow(format!(
+                            "{} doesn't fit in a {}",
+                            bits,
+                            stringify!($int)
+                        )));
+                    }
+
+                    Ok(bits.iter().fold(0, |acc, x| acc << 1 | x as $int))
+                }
+            }
+        )*
+    };
+}
+
+try_into_integer!(u8, u16, u32, u64, u128);
@@ -0,0 +1,237 @@
+use std::str::FromStr;
+use crate::{Error, Result};
+use super::{check_diagnostic, check_diagnostics, Bits};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Diagnostics packed into bitsets, one per bit position, with a bit for
+/// each sample. Counting a column is a popcount per 64 samples, and
+/// narrowing down the life support candidates is a mask per 64 samples,
+/// rather than picking through strings a character at a time.
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day3::PackedDiagnostics;
+/// let diagnostics: PackedDiagnostics = "00100\n11110\n10110\n10111\n10101\n01111\n\
+///                                       00111\n11100\n10000\n11001\n00010\n01010".parse()?;
+///
+/// assert_eq!(diagnostics.len(), 12);
+/// assert_eq!(diagnostics.ones(0), 7);
+/// assert_eq!(diagnostics.gamma_rate()?, 22);
+/// assert_eq!(diagnostics.epsilon_rate()?, 9);
+/// assert_eq!(diagnostics.rate_life_support()?, (23, 10));
+/// # Ok::<(), aoc2021::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PackedDiagnostics {
+    width: usize,
+    len: usize,
+    /// For each bit position, most significant first, which samples have
+    /// that bit set
+    columns: Vec<Vec<u64>>,
+}
+
+impl PackedDiagnostics {
+    /// Pack the samples, which are checked as by `check_diagnostics`
+    /// unless there are none at all
+    pub fn new(samples: &[&str]) -> Result<Self> {
+        if samples.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let width = check_diagnostics(samples)?;
+        let words = (samples.len() + WORD_BITS - 1) / WORD_BITS;
+        let mut columns = vec![vec![0; words]; width];
+
+        for (idx, sample) in samples.iter().enumerate() {
+            for (bit, _) in sample.bytes().enumerate().filter(|&(_, x)| x == b'1') {
+                columns[bit][idx / WORD_BITS] |= 1 << (idx % WORD_BITS);
+            }
+        }
+
+        Ok(Self { width, len: samples.len(), columns })
+    }
+
+    /// How many bits wide each sample is
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// How many samples there are
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// How many samples have the given bit set, counting from the most
+    /// significant
+    ///
+    /// # Panics
+    ///
+    /// If `bit` isn't less than the width of the samples
+    pub fn ones(&self, bit: usize) -> usize {
+        self.columns[bit].iter().map(|x| x.count_ones() as usize).sum()
+    }
+
+    /// One of the samples, unpacked again
+    ///
+    /// # Panics
+    ///
+    /// If `idx` isn't less than the number of samples
+    pub fn sample(&self, idx: usize) -> Bits {
+        assert!(idx < self.len, "Sample {} is out of range for {} samples", idx, self.len);
+        self.columns
+            .iter()
+            .map(|column| column[idx / WORD_BITS] >> (idx % WORD_BITS) & 1 == 1)
+            .collect()
+    }
+
+    /// The most common bit in each position
+    pub fn gamma(&self) -> Bits {
+        (0..self.width).map(|bit| self.ones(bit) > self.len / 2).collect()
+    }
+
+    /// The least common bit in each position
+    pub fn epsilon(&self) -> Bits {
+        self.gamma().complement()
+    }
+
+    pub fn gamma_rate(&self) -> Result<u64> {
+        u64::try_from(&self.gamma())
+    }
+
+    pub fn epsilon_rate(&self) -> Result<u64> {
+        u64::try_from(&self.epsilon())
+    }
+
+    /// The O2 generator and CO2 scrubber ratings, however wide they are
+    pub fn life_support_ratings(&self) -> Result<(Bits, Bits)> {
+        if self.is_empty() {
+            return Err(Error::NoAnswer("No diagnostics to rate".into()));
+        }
+
+        Ok((self.sample(self.rating(true)), self.sample(self.rating(false))))
+    }
+
+    /// Rate the life support system, producing the O2 generator rating and
+    /// the CO2 scrubber rating. Fails if the ratings are too wide to fit.
+    pub fn rate_life_support(&self) -> Result<(u64, u64)> {
+        let (o2, co2) = self.life_support_ratings()?;
+
+        Ok((u64::try_from(&o2)?, u64::try_from(&co2)?))
+    }
+
+    /// Narrow the samples down a bit at a time, keeping those with the
+    /// most common bit (ties going to 1) or the least common (ties going
+    /// to 0), until there's only one left. Returns its index.
+    fn rating(&self, most_common: bool) -> usize {
+        let mut mask = vec![!0_u64; self.len / WORD_BITS];
+        if self.len % WORD_BITS != 0 {
+            mask.push((1 << (self.len % WORD_BITS)) - 1);
+        }
+
+        let mut remaining = self.len;
+        for column in &self.columns {
+            if remaining <= 1 {
+                break;
+            }
+
+            let ones: usize = mask.iter().zip(column).map(|(m, x)| (m & x).count_ones() as usize).sum();
+            let keep_ones = (ones >= remaining - ones) == most_common;
+            let kept = if keep_ones { ones } else { remaining - ones };
+
+            // If none of the samples left have the bit we're after, there's
+            // nothing to narrow down
+            if kept == 0 {
+                continue;
+            }
+
+            for (m, x) in mask.iter_mut().zip(column) {
+                *m &= if keep_ones { *x } else { !*x };
+            }
+            remaining = kept;
+        }
+
+        mask.iter()
+            .position(|&x| x != 0)
+            .map_or(0, |word| word * WORD_BITS + mask[word].trailing_zeros() as usize)
+    }
+}
+
+impl FromStr for PackedDiagnostics {
+    type Err = Error;
+
+    /// Parses one diagnostic per line, skipping blank lines, and reporting
+    /// the line and column of anything that isn't binary or is the wrong
+    /// width
+    fn from_str(s: &str) -> Result<Self> {
+        let lines: Vec<(usize, &str)> = s.lines().enumerate().filter(|(_, x)| !x.trim().is_empty()).collect();
+
+        if let Some(&(_, first)) = lines.first() {
+            for &(n, line) in &lines {
+                check_diagnostic(line, Some(first.len())).map_err(|e| e.at_line(n + 1))?;
+            }
+        }
+
+        let samples: Vec<&str> = lines.into_iter().map(|(_, x)| x).collect();
+        Self::new(&samples)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day3::{rate_life_support, PowerConsumption};
+    use crate::inputs::{Inputs, Kind, DEFAULT_INPUT_DIR};
+
+    #[test]
+    fn matches_strings() {
+        let input = Inputs::new(DEFAULT_INPUT_DIR).load(3, Kind::Puzzle).unwrap();
+        let mut samples: Vec<&str> = input.lines().collect();
+        let packed = PackedDiagnostics::new(&samples).unwrap();
+
+        let mut pc = PowerConsumption::new();
+        for sample in &samples {
+            pc.add_diagnostic(sample).unwrap();
+        }
+
+        assert_eq!(packed.gamma(), pc.gamma());
+        assert_eq!(packed.epsilon(), pc.epsilon());
+        assert_eq!(packed.rate_life_support().unwrap(), rate_life_support(&mut samples).unwrap());
+    }
+
+    #[test]
+    fn edge_cases() {
+        assert!(PackedDiagnostics::default().rate_life_support().is_err());
+        assert!("01\n011".parse::<PackedDiagnostics>().is_err());
+
+        // Blank lines are skipped, but still count toward line numbers
+        let diagnostics: PackedDiagnostics = "01\n\n11\n\n".parse().unwrap();
+        assert_eq!(diagnostics.len(), 2);
+        let error = "01\n\n1x\n".parse::<PackedDiagnostics>().unwrap_err();
+        assert_eq!(error.to_string(), "line 3, column 2: Not a binary digit: x");
+
+        // Samples that agree on a bit don't leave the CO2 rating empty
+        let diagnostics: PackedDiagnostics = "110\n101\n100".parse().unwrap();
+        assert_eq!(diagnostics.rate_life_support().unwrap(), (0b101, 0b110));
+
+        // More than a word's worth of samples
+        let samples: Vec<String> = (0..200_u32).map(|x| format!("{:08b}", x)).collect();
+        let mut samples: Vec<&str> = samples.iter().map(String::as_str).collect();
+        let packed = PackedDiagnostics::new(&samples).unwrap();
+        assert_eq!(packed.sample(130).to_string(), "10000010");
+        assert_eq!(packed.rate_life_support().unwrap(), rate_life_support(&mut samples).unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range for 200 samples")]
+    fn sample_out_of_range() {
+        let samples: Vec<String> = (0..200_u32).map(|x| format!("{:08b}", x)).collect();
+        let samples: Vec<&str> = samples.iter().map(String::as_str).collect();
+        PackedDiagnostics::new(&samples).unwrap().sample(200);
+    }
+}
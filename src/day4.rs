@@ -1,53 +1,95 @@
-use std::collections::HashMap;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::str::FromStr;
+use crate::error::column_of;
+use crate::grid::Grid;
+use crate::{Error, Result, Solution};
 
-pub struct Coords {
-    pub row: usize,
-    pub col: usize,
-}
+pub use crate::grid::Coords;
 
-impl Coords {
-    pub fn new(row: usize, col: usize) -> Self {
-        Self { row, col }
-    }
-}
+/// How many numbers there are along each side of a card
+const SIZE: usize = 5;
 
-#[derive(Default)]
+#[derive(Clone)]
 pub struct BingoCard {
-    spots: HashMap<u32, Coords>,
-    row_counts: [u32; 5],
-    col_counts: [u32; 5],
+    numbers: Grid<u32>,
+    marked: Grid<bool>,
     still_playing: bool,
 }
 
+impl Default for BingoCard {
+    fn default() -> Self {
+        Self {
+            numbers: Grid::new(SIZE, SIZE, 0).expect("Bingo cards are small"),
+            marked: Grid::new(SIZE, SIZE, false).expect("Bingo cards are small"),
+            still_playing: false,
+        }
+    }
+}
+
 impl BingoCard {
-    pub fn from_lines(input: &[&str]) -> Self {
+    /// Build a card from five lines of five whitespace separated numbers.
+    /// Any line numbers in the returned error are relative to the first
+    /// line of the card.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aoc2021::day4::BingoCard;
+    /// assert!(BingoCard::from_lines(&["22 13 17 11 0", "8 2 23 4 24", "21 9 14 16 7", "6 10 3 18 5", "1 12 20 15 19"]).is_ok());
+    ///
+    /// assert!(BingoCard::from_lines(&["22 13 17 11 0", "8 2 23 4 24"]).is_err());
+    /// assert!(BingoCard::from_lines(&["22 13 17 11 0", "8 2 23 4", "21 9 14 16 7", "6 10 3 18 5", "1 12 20 15 19"]).is_err());
+    /// assert!(BingoCard::from_lines(&["22 13 17 11 x", "8 2 23 4 24", "21 9 14 16 7", "6 10 3 18 5", "1 12 20 15 19"]).is_err());
+    /// assert!(BingoCard::from_lines(&["22 13 17 11 0", "8 2 23 4 22", "21 9 14 16 7", "6 10 3 18 5", "1 12 20 15 19"]).is_err());
+    /// ```
+    pub fn from_lines(input: &[&str]) -> Result<Self> {
+        if input.len() != SIZE {
+            return Err(Error::parse(format!("Bingo cards have {} rows, not {}", SIZE, input.len())));
+        }
+
         let mut card = Self {
             still_playing: true,
             ..Default::default()
         };
 
-        for (n, line) in input.iter().enumerate().take(5) {
-            line
-                .split_whitespace()
-                .enumerate()
-                .for_each(|(idx, val)| card.add_spot(val.parse().unwrap_or(0), Coords::new(n, idx)));
+        let mut seen = HashSet::new();
+        for (n, line) in input.iter().enumerate() {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            if tokens.len() != SIZE {
+                return Err(Error::parse(format!("Bingo cards have {} columns, not {}", SIZE, tokens.len())).at_line(n + 1));
+            }
+
+            for (idx, val) in tokens.into_iter().enumerate() {
+                let at = |e: Error| e.at_column(column_of(line, val)).at_line(n + 1);
+                let number = val.parse().map_err(|_| at(Error::parse(format!("Bad bingo number: {}", val))))?;
+                if !seen.insert(number) {
+                    return Err(at(Error::parse(format!("{} appears twice on the same card", number))));
+                }
+
+                card.add_spot(number, Coords::new(n, idx));
+            }
         }
 
-        card
+        Ok(card)
     }
 
     pub fn add_spot(&mut self, number: u32, loc: Coords) {
-        self.row_counts[loc.row] += 1;
-        self.col_counts[loc.col] += 1;
-        self.spots.insert(number, loc);
+        self.numbers[loc] = number;
+        self.marked[loc] = false;
     }
 
     pub fn mark_spot(&mut self, number: u32) -> bool {
-        if let Some(loc) = self.spots.remove(&number) {
-            self.row_counts[loc.row] -= 1;
-            self.col_counts[loc.col] -= 1;
+        let marked = &self.marked;
+        let found = self.numbers
+            .iter()
+            .find(|&(loc, &x)| x == number && !marked[loc])
+            .map(|(loc, _)| loc);
+
+        if let Some(loc) = found {
+            self.marked[loc] = true;
 
-            self.row_counts[loc.row] == 0 || self.col_counts[loc.col] == 0
+            self.marked.row(loc.row).iter().all(|&x| x) || self.marked.column(loc.col).all(|&x| x)
         } else {
             false
         }
@@ -55,7 +97,19 @@ impl BingoCard {
 
     pub fn declare_victory(&mut self) -> u32 {
         self.still_playing = false;
-        self.spots.keys().sum()
+        self.numbers
+            .iter()
+            .filter(|&(loc, _)| !self.marked[loc])
+            .map(|(_, &x)| x)
+            .sum()
+    }
+}
+
+impl TryFrom<&[&str]> for BingoCard {
+    type Error = Error;
+
+    fn try_from(input: &[&str]) -> Result<Self> {
+        Self::from_lines(input)
     }
 }
 
@@ -67,13 +121,14 @@ impl BingoCard {
 /// use aoc2021::day4::*;
 /// let numbers = vec![7, 4, 9, 5, 11, 17, 23, 2, 0, 14, 21, 24, 10, 16, 13, 6, 15, 25, 12, 22, 18, 20, 8, 19, 3, 26, 1];
 ///
-/// let card1 = BingoCard::from_lines(&["22 13 17 11 0", "8 2 23 4 24", "21 9 14 16 7", "6 10 3 18 5", "1 12 20 15 19"]);
-/// let card2 = BingoCard::from_lines(&["3 15 0 2 22", "9 18 13 17 5", "19 8 7 25 23", "20 11 10 24 4", "14 21 16 12 6"]);
-/// let card3 = BingoCard::from_lines(&["14 21 17 24 4", "10 16 15 9 19", "18 8 23 26 20", "22 11 13 6 5", "2 0 12 3 7"]);
+/// let card1 = BingoCard::from_lines(&["22 13 17 11 0", "8 2 23 4 24", "21 9 14 16 7", "6 10 3 18 5", "1 12 20 15 19"])?;
+/// let card2 = BingoCard::from_lines(&["3 15 0 2 22", "9 18 13 17 5", "19 8 7 25 23", "20 11 10 24 4", "14 21 16 12 6"])?;
+/// let card3 = BingoCard::from_lines(&["14 21 17 24 4", "10 16 15 9 19", "18 8 23 26 20", "22 11 13 6 5", "2 0 12 3 7"])?;
 ///
 /// let results = play_bingo(vec![card1, card2, card3], numbers);
 /// assert_eq!(results.first(), Some(&4512));
 /// assert_eq!(results.last(), Some(&1924));
+/// # Ok::<(), aoc2021::Error>(())
 /// ```
 pub fn play_bingo(mut cards: Vec<BingoCard>, numbers: Vec<u32>) -> Vec<u32> {
     let mut winning_scores = vec![];
@@ -89,33 +144,97 @@ pub fn play_bingo(mut cards: Vec<BingoCard>, numbers: Vec<u32>) -> Vec<u32> {
     winning_scores
 }
 
-#[cfg(test)]
-mod answers {
-    use super::*;
+/// The numbers that will be called, and the cards in play
+pub struct Game {
+    pub numbers: Vec<u32>,
+    pub cards: Vec<BingoCard>,
+}
 
-    #[test]
-    fn puzzle1_and_puzzle2() {
-        // Parse the input file - the first line is the list of numbers called,
-        // then some number of bingo cards, all separated by whitespace
-        let mut input: Vec<&str> = include_str!("./input/day4")
+impl FromStr for Game {
+    type Err = Error;
+
+    /// The first line is the list of numbers called, then some number
+    /// of bingo cards, all separated by whitespace
+    fn from_str(input: &str) -> Result<Self> {
+        let mut lines = input
             .split_terminator('\n')
-            .filter(|x| !x.trim().is_empty())
-            .collect();
+            .enumerate()
+            .filter(|(_, x)| !x.trim().is_empty());
+
+        let (n, first) = lines
+            .next()
+            .ok_or_else(|| Error::parse("Missing the list of numbers to call"))?;
 
-        let numbers: Vec<u32> = input.remove(0)
+        let numbers = first
             .split_terminator(',')
-            .map(|x| x.parse().unwrap_or(0))
-            .collect();
+            .map(|x| {
+                x.trim()
+                    .parse()
+                    .map_err(|_| Error::parse(format!("Bad bingo number: {}", x.trim())).at_column(column_of(first, x)).at_line(n + 1))
+            })
+            .collect::<Result<_>>()?;
+
+        let lines: Vec<(usize, &str)> = lines.collect();
+        let mut cards = vec![];
+        for chunk in lines.chunks(5) {
+            let first_line = chunk[0].0;
+            let rows: Vec<&str> = chunk.iter().map(|&(_, x)| x).collect();
+            let card = BingoCard::from_lines(&rows).map_err(|e| e.offset_lines(first_line).at_line(first_line + 1))?;
+            cards.push(card);
+        }
 
-        let mut cards: Vec<BingoCard> = vec![];
+        Ok(Game { numbers, cards })
+    }
+}
 
-        for chunk in input.chunks_exact(5) {
-            cards.push(BingoCard::from_lines(chunk));
-        }
+pub struct Day4;
 
+impl Solution for Day4 {
+    type Input = Game;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        input.parse()
+    }
+
+    fn part1(input: &Self::Input) -> Result<u64> {
+        let results = play_bingo(input.cards.clone(), input.numbers.clone());
+        let score = results.first().ok_or_else(|| Error::NoAnswer("Nobody won at bingo".into()))?;
+        Ok(*score as u64)
+    }
+
+    fn part2(input: &Self::Input) -> Result<u64> {
+        let results = play_bingo(input.cards.clone(), input.numbers.clone());
+        let score = results.last().ok_or_else(|| Error::NoAnswer("Nobody won at bingo".into()))?;
+        Ok(*score as u64)
+    }
+}
+
+#[cfg(test)]
+mod answers {
+    use super::*;
+    use crate::inputs::{Inputs, Kind, DEFAULT_INPUT_DIR};
+
+    #[test]
+    fn puzzle1_and_puzzle2() {
+        let Game { numbers, cards } = Inputs::new(DEFAULT_INPUT_DIR).load(4, Kind::Puzzle).unwrap().parse().unwrap();
         let results = play_bingo(cards, numbers);
 
         assert_eq!(results.first(), Some(&82440));
         assert_eq!(results.last(), Some(&20774));
     }
+
+    #[test]
+    fn malformed() {
+        let error = "1,2,x".parse::<Game>().err().unwrap();
+        assert_eq!(error.to_string(), "line 1, column 5: Bad bingo number: x");
+
+        let error = "1,2\n\n1 2 3 4 5\n6 7 8 9 10\n11 12 13 14 15\n16 17 18 19 20\n21 22 23 24 2"
+            .parse::<Game>()
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "line 7, column 13: 2 appears twice on the same card");
+
+        let error = "1,2\n\n1 2 3 4 5\n6 7 8 9 10".parse::<Game>().err().unwrap();
+        assert_eq!(error.to_string(), "line 3: Bingo cards have 5 rows, not 2");
+    }
 }
@@ -1,6 +1,12 @@
 use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::str::FromStr;
+use crate::error::column_of;
+use crate::grid::{Coords, Grid};
+use crate::{Error, Result, Solution};
 
+#[derive(Clone, Debug)]
 pub struct Line {
     start: (u32, u32),
     end: (u32, u32),
@@ -15,7 +21,10 @@ impl Line {
         let x_iter = Self::range_iterator(self.start.0, self.end.0);
         let y_iter = Self::range_iterator(self.start.1, self.end.1);
 
-        x_iter.zip(y_iter)
+        // Either range can repeat forever, so stop after the longer one
+        let len = self.start.0.max(self.end.0) - self.start.0.min(self.end.0);
+        let len = len.max(self.start.1.max(self.end.1) - self.start.1.min(self.end.1));
+        x_iter.zip(y_iter).take(len as usize + 1)
     }
 
     pub fn range_iterator(a: u32, b: u32) -> Box<dyn Iterator<Item=u32>> {
@@ -34,29 +43,164 @@ impl Line {
         self.start.1 == self.end.1
     }
 
-    fn str_to_point(s: &str) -> (u32, u32) {
-        let mut tokens = s.split_terminator(',');
-        (tokens.next().unwrap_or("0").parse().unwrap_or(0), tokens.next().unwrap_or("0").parse().unwrap_or(0))
+    /// Parse a point from `s`, which is a token within `line`
+    fn str_to_point(line: &str, s: &str) -> Result<(u32, u32)> {
+        let (x, y) = s
+            .split_once(',')
+            .ok_or_else(|| Error::parse(format!("Points are written as x,y, not {}", s)).at_column(column_of(line, s)))?;
+
+        let coord = |v: &str| v.parse().map_err(|_| Error::parse(format!("Bad coordinate: {}", v)).at_column(column_of(line, v)));
+        Ok((coord(x)?, coord(y)?))
     }
 }
 
-impl From<&str> for Line {
-    fn from(input: &str) -> Self {
+impl FromStr for Line {
+    type Err = Error;
+
+    /// Parses a line segment written as `x1,y1 -> x2,y2`. Only horizontal,
+    /// vertical and 45 degree diagonal lines are allowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aoc2021::day5::Line;
+    /// assert!("0,9 -> 5,9".parse::<Line>()?.is_horizontal());
+    /// assert!("7,0 -> 7,4".parse::<Line>()?.is_vertical());
+    /// assert!("0,0 -> 8,8".parse::<Line>().is_ok());
+    ///
+    /// assert!("0,9 5,9".parse::<Line>().is_err());
+    /// assert!("0,9 -> 5".parse::<Line>().is_err());
+    /// assert!("0,x -> 5,9".parse::<Line>().is_err());
+    /// assert!("0,0 -> 8,7".parse::<Line>().is_err());
+    /// # Ok::<(), aoc2021::Error>(())
+    /// ```
+    fn from_str(input: &str) -> Result<Self> {
         let tokens = input.split_whitespace().collect::<Vec<_>>();
-        Self::new(Self::str_to_point(tokens[0]), Self::str_to_point(tokens[2]))
+        if tokens.len() != 3 || tokens[1] != "->" {
+            return Err(Error::parse("Lines are written as x1,y1 -> x2,y2"));
+        }
+
+        let line = Self::new(Self::str_to_point(input, tokens[0])?, Self::str_to_point(input, tokens[2])?);
+
+        let dx = (line.start.0 as i64 - line.end.0 as i64).abs();
+        let dy = (line.start.1 as i64 - line.end.1 as i64).abs();
+        if dx != 0 && dy != 0 && dx != dy {
+            return Err(Error::parse("Lines must be horizontal, vertical or diagonal"));
+        }
+
+        Ok(line)
+    }
+}
+
+impl TryFrom<&str> for Line {
+    type Error = Error;
+
+    fn try_from(input: &str) -> Result<Self> {
+        input.parse()
     }
+}
 
+/// The dense map is given up on for a sparse one once the box around the
+/// lines has more than this many cells for each point the lines cover...
+const SPARSE_AFTER: u128 = 16;
+/// ...and more than this many cells altogether
+const DENSE_CELLS: u128 = 1 << 20;
+
+/// How many vents cover each point
+enum VentMap {
+    /// Every point in the box around the lines, with the top left cell at
+    /// `origin`
+    Dense { origin: (u32, u32), grid: Grid<u32> },
+    /// Only the points some line covers
+    Sparse(HashMap<(u32, u32), u32>),
+}
+
+impl Default for VentMap {
+    fn default() -> Self {
+        Self::Dense { origin: (0, 0), grid: Grid::default() }
+    }
 }
 
 #[derive(Default)]
 pub struct OceanFloor {
-    vent_map: HashMap<(u32, u32), u32>
+    vent_map: VentMap,
+    /// How many points the lines cover, counting overlaps every time
+    covered: u128,
 }
 
 impl OceanFloor {
-    pub fn add_vent(&mut self, line: Line) {
-        for point in line.points() {
-            *self.vent_map.entry(point).or_insert(0) += 1;
+    /// Mark the points a line covers. The map is kept dense over the box
+    /// around the lines added so far, unless the lines are spread so
+    /// thinly that most of it would be empty. Fails if there isn't the
+    /// memory for the map.
+    pub fn add_vent(&mut self, line: Line) -> Result<()> {
+        self.covered += line.clone().points().count() as u128;
+
+        if let VentMap::Dense { origin, grid } = &mut self.vent_map {
+            // Grow the map to fit the line. x picks the column and y the row.
+            let mut low = (line.start.0.min(line.end.0), line.start.1.min(line.end.1));
+            let mut high = (line.start.0.max(line.end.0), line.start.1.max(line.end.1));
+            let (width, height) = (grid.width(), grid.height());
+            if width == 0 || height == 0 {
+                *origin = low;
+            } else {
+                low = (low.0.min(origin.0), low.1.min(origin.1));
+                high = (high.0.max(origin.0 + (width - 1) as u32), high.1.max(origin.1 + (height - 1) as u32));
+            }
+
+            let size = ((high.0 - low.0) as usize + 1, (high.1 - low.1) as usize + 1);
+            let cells = size.0 as u128 * size.1 as u128;
+
+            if cells > DENSE_CELLS && cells > SPARSE_AFTER * self.covered {
+                let vents = grid
+                    .iter()
+                    .filter(|(_, &count)| count > 0)
+                    .map(|(at, &count)| ((origin.0 + at.col as u32, origin.1 + at.row as u32), count))
+                    .collect();
+                self.vent_map = VentMap::Sparse(vents);
+            } else if low != *origin || size != (width, height) {
+                let mut map = Grid::new(size.0, size.1, 0)?;
+                let shift = ((origin.0 - low.0) as usize, (origin.1 - low.1) as usize);
+                for (at, &count) in grid.iter() {
+                    map[Coords::new(at.row + shift.1, at.col + shift.0)] = count;
+                }
+
+                *origin = low;
+                *grid = map;
+            }
+        }
+
+        match &mut self.vent_map {
+            VentMap::Dense { origin, grid } => {
+                for (x, y) in line.points() {
+                    grid[Coords::new((y - origin.1) as usize, (x - origin.0) as usize)] += 1;
+                }
+            }
+            VentMap::Sparse(vents) => {
+                for point in line.points() {
+                    *vents.entry(point).or_default() += 1;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// How many vents cover each point of the ocean floor, for the points
+    /// in the box around every line, unless the lines are too spread out
+    /// for that to be kept
+    pub fn vents(&self) -> Option<&Grid<u32>> {
+        match &self.vent_map {
+            VentMap::Dense { grid, .. } => Some(grid),
+            VentMap::Sparse(_) => None,
+        }
+    }
+
+    /// The x and y of the point in the top left cell of `vents`
+    pub fn origin(&self) -> Option<(u32, u32)> {
+        match &self.vent_map {
+            VentMap::Dense { origin, .. } => Some(*origin),
+            VentMap::Sparse(_) => None,
         }
     }
 
@@ -67,39 +211,75 @@ impl OceanFloor {
 ///
 /// ```
 /// use aoc2021::day5::*;
+/// use std::convert::TryFrom;
 /// let mut floor = OceanFloor::default();
-/// floor.add_vent(Line::from("0,9 -> 5,9"));
-/// floor.add_vent(Line::from("8,0 -> 0,8"));
-/// floor.add_vent(Line::from("9,4 -> 3,4"));
-/// floor.add_vent(Line::from("2,2 -> 2,1"));
-/// floor.add_vent(Line::from("7,0 -> 7,4"));
-/// floor.add_vent(Line::from("6,4 -> 2,0"));
-/// floor.add_vent(Line::from("0,9 -> 2,9"));
-/// floor.add_vent(Line::from("3,4 -> 1,4"));
-/// floor.add_vent(Line::from("0,0 -> 8,8"));
-/// floor.add_vent(Line::from("5,5 -> 8,2"));
+/// floor.add_vent(Line::try_from("0,9 -> 5,9")?)?;
+/// floor.add_vent(Line::try_from("8,0 -> 0,8")?)?;
+/// floor.add_vent(Line::try_from("9,4 -> 3,4")?)?;
+/// floor.add_vent(Line::try_from("2,2 -> 2,1")?)?;
+/// floor.add_vent(Line::try_from("7,0 -> 7,4")?)?;
+/// floor.add_vent(Line::try_from("6,4 -> 2,0")?)?;
+/// floor.add_vent(Line::try_from("0,9 -> 2,9")?)?;
+/// floor.add_vent(Line::try_from("3,4 -> 1,4")?)?;
+/// floor.add_vent(Line::try_from("0,0 -> 8,8")?)?;
+/// floor.add_vent(Line::try_from("5,5 -> 8,2")?)?;
 ///
 /// assert_eq!(floor.count_overlap(), 12);
+/// # Ok::<(), aoc2021::Error>(())
     pub fn count_overlap(&self) -> usize {
-        self.vent_map
-            .values()
-            .filter(|&x| *x > 1)
-            .count()
+        match &self.vent_map {
+            VentMap::Dense { grid, .. } => grid.values().filter(|&x| *x > 1).count(),
+            VentMap::Sparse(vents) => vents.values().filter(|&x| *x > 1).count(),
+        }
+    }
+}
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Input = Vec<Line>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        input
+            .lines()
+            .enumerate()
+            .filter(|(_, x)| !x.trim().is_empty())
+            .map(|(n, line)| line.parse().map_err(|e: Error| e.at_line(n + 1)))
+            .collect()
+    }
+
+    fn part1(input: &Self::Input) -> Result<u64> {
+        let mut ocean = OceanFloor::default();
+        for line in input.iter().filter(|x| x.is_vertical() || x.is_horizontal()) {
+            ocean.add_vent(line.clone())?;
+        }
+
+        Ok(ocean.count_overlap() as u64)
+    }
+
+    fn part2(input: &Self::Input) -> Result<u64> {
+        let mut ocean = OceanFloor::default();
+        for line in input {
+            ocean.add_vent(line.clone())?;
+        }
+
+        Ok(ocean.count_overlap() as u64)
     }
 }
 
 #[cfg(test)]
 mod answers {
     use super::*;
+    use crate::inputs::{Inputs, Kind, DEFAULT_INPUT_DIR};
 
     #[test]
     fn puzzle1() {
         let mut ocean = OceanFloor::default();
 
-        for line in include_str!("./input/day5").lines() {
-            let line = Line::from(line);
+        for line in Inputs::new(DEFAULT_INPUT_DIR).load(5, Kind::Puzzle).unwrap().lines() {
+            let line: Line = line.parse().unwrap();
             if line.is_vertical() || line.is_horizontal() {
-                ocean.add_vent(line);
+                ocean.add_vent(line).unwrap();
             }
         }
 
@@ -110,11 +290,64 @@ mod answers {
     fn puzzle2() {
         let mut ocean = OceanFloor::default();
 
-        for line in include_str!("./input/day5").lines() {
-            let line = Line::from(line);
-            ocean.add_vent(line);
+        for line in Inputs::new(DEFAULT_INPUT_DIR).load(5, Kind::Puzzle).unwrap().lines() {
+            let line: Line = line.parse().unwrap();
+            ocean.add_vent(line).unwrap();
         }
 
         assert_eq!(ocean.count_overlap(), 19349);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn floor(lines: &[&str]) -> OceanFloor {
+        let mut floor = OceanFloor::default();
+        for line in lines {
+            floor.add_vent(line.parse().unwrap()).unwrap();
+        }
+
+        floor
+    }
+
+    #[test]
+    fn blank_lines() {
+        assert_eq!(Day5::parse("0,9 -> 5,9\n\n8,0 -> 0,8\n\n").unwrap().len(), 2);
+
+        let error = Day5::parse("0,9 -> 5,9\n\n8,0 0,8\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 3: Lines are written as x1,y1 -> x2,y2");
+    }
+
+    #[test]
+    fn bounding_box() {
+        let far = floor(&["100000,100000 -> 100000,100000"]);
+        assert_eq!(far.origin(), Some((100000, 100000)));
+        let vents = far.vents().unwrap();
+        assert_eq!((vents.width(), vents.height()), (1, 1));
+
+        let grown = floor(&["5,5 -> 6,6", "3,7 -> 5,5"]);
+        assert_eq!(grown.origin(), Some((3, 5)));
+        assert_eq!(grown.vents().unwrap().to_string(), "0 0 2 0\n0 1 0 1\n1 0 0 0\n");
+        assert_eq!(grown.count_overlap(), 1);
+    }
+
+    #[test]
+    fn far_apart() {
+        let corners = floor(&["0,0 -> 0,0", "60000,60000 -> 60000,60000"]);
+        assert_eq!(corners.vents(), None);
+        assert_eq!(corners.count_overlap(), 0);
+
+        // Overlaps found while the map was dense carry over to the sparse one
+        let spread = floor(&[
+            "0,0 -> 2,2",
+            "2,0 -> 0,2",
+            "3000000,5 -> 3000002,5",
+            "3000001,4 -> 3000001,6",
+            "4294967295,4294967295 -> 4294967295,4294967295",
+        ]);
+        assert_eq!(spread.origin(), None);
+        assert_eq!(spread.count_overlap(), 2);
+    }
+}
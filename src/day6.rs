@@ -1,3 +1,6 @@
+use crate::error::column_of;
+use crate::{Error, Result, Solution};
+
 /// Simulate a population of lanternfish based on the lifecycle
 /// rules given
 /// - Newborns take 8 days before producing offspring
@@ -18,13 +21,70 @@ pub fn be_fruitful(population: &mut [u64; 9]) {
     population[6] += population[8];
 }
 
+/// Run the simulation for the given number of days, and count the
+/// resulting population
+pub fn simulate(mut population: [u64; 9], days: usize) -> u64 {
+    for _ in 1..=days {
+        be_fruitful(&mut population);
+    }
+
+    population.iter().sum()
+}
+
+/// Parse a comma separated list of lanternfish ages into a count of how
+/// many fish there are of each age
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day6::parse_population;
+/// assert_eq!(parse_population("3,4,3,1,2")?, [0, 1, 1, 2, 1, 0, 0, 0, 0]);
+///
+/// assert!(parse_population("3,4,x").is_err());
+/// assert!(parse_population("3,4,9").is_err());
+/// # Ok::<(), aoc2021::Error>(())
+/// ```
+pub fn parse_population(input: &str) -> Result<[u64; 9]> {
+    let mut population = [0; 9];
+    for x in input.trim().split_terminator(',') {
+        let at = |e: Error| e.at_column(column_of(input, x.trim()));
+        let age: usize = x.trim().parse().map_err(|_| at(Error::parse(format!("Bad lanternfish age: {}", x.trim()))))?;
+        if age >= population.len() {
+            return Err(at(Error::parse(format!("Lanternfish age out of range: {}", age))));
+        }
+
+        population[age] += 1;
+    }
+
+    Ok(population)
+}
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    type Input = [u64; 9];
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_population(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<u64> {
+        Ok(simulate(*input, 80))
+    }
+
+    fn part2(input: &Self::Input) -> Result<u64> {
+        Ok(simulate(*input, 256))
+    }
+}
+
 #[cfg(test)]
 mod answers {
     use super::*;
+    use crate::inputs::{Inputs, Kind, DEFAULT_INPUT_DIR};
 
     #[test]
     fn puzzle1() {
-        let mut population = include_str!("./input/day6")
+        let mut population = Inputs::new(DEFAULT_INPUT_DIR).load(6, Kind::Puzzle).unwrap()
             .split_terminator(',')
             .map(|x| { x.trim().parse::<usize>().unwrap() })
             .fold([0; 9], |mut acc, x| { acc[x] += 1; acc });
@@ -38,7 +98,7 @@ mod answers {
 
     #[test]
     fn puzzle2() {
-        let mut population = include_str!("./input/day6")
+        let mut population = Inputs::new(DEFAULT_INPUT_DIR).load(6, Kind::Puzzle).unwrap()
             .split_terminator(',')
             .map(|x| { x.trim().parse::<usize>().unwrap() })
             .fold([0; 9], |mut acc, x| { acc[x] += 1; acc });
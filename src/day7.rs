@@ -1,3 +1,6 @@
+use crate::error::column_of;
+use crate::{Error, Result, Solution};
+
 pub fn constant_burn(x: u32) -> u32 {
     x
 }
@@ -6,6 +9,10 @@ pub fn linear_burn(x: u32) -> u32 {
     (x * (x + 1)) / 2
 }
 
+fn distance(a: u32, b: u32) -> u32 {
+    if a > b { a - b } else { b - a }
+}
+
 pub fn geometric_median(values: &[u32]) -> u32 {
     let mut copied = values.to_vec();
     copied.sort_unstable();
@@ -28,17 +35,65 @@ where B: Fn(u32) -> u32, M: Fn(&[u32]) -> u32 {
 
     let x = values
         .iter()
-        .fold(0, |acc, x| acc + burn_rate(x.abs_diff(median)));
+        .fold(0, |acc, x| acc + burn_rate(distance(*x, median)));
     let y = values
         .iter()
-        .fold(0, |acc, x| acc + burn_rate(x.abs_diff(median+1)));
+        .fold(0, |acc, x| acc + burn_rate(distance(*x, median+1)));
 
     if x < y { x } else { y }
 }
 
+/// Parse a comma separated list of crab positions
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day7::parse_crabs;
+/// assert_eq!(parse_crabs("16,1,2")?, vec![16, 1, 2]);
+/// assert!(parse_crabs("16,-1,2").is_err());
+/// # Ok::<(), aoc2021::Error>(())
+/// ```
+pub fn parse_crabs(input: &str) -> Result<Vec<u32>> {
+    input
+        .trim()
+        .split_terminator(',')
+        .map(|x| {
+            x.trim()
+                .parse()
+                .map_err(|_| Error::parse(format!("Bad crab position: {}", x.trim())).at_column(column_of(input, x.trim())))
+        })
+        .collect()
+}
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    type Input = Vec<u32>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let crabs = parse_crabs(input)?;
+        if crabs.is_empty() {
+            return Err(Error::parse("There are no crabs"));
+        }
+
+        Ok(crabs)
+    }
+
+    fn part1(input: &Self::Input) -> Result<u64> {
+        let mut crabs = input.clone();
+        Ok(optimize_crabs(&mut crabs, geometric_median, constant_burn) as u64)
+    }
+
+    fn part2(input: &Self::Input) -> Result<u64> {
+        let mut crabs = input.clone();
+        Ok(optimize_crabs(&mut crabs, arithmetic_mean, linear_burn) as u64)
+    }
+}
+
 #[cfg(test)]
 mod answers {
     use super::*;
+    use crate::inputs::{Inputs, Kind, DEFAULT_INPUT_DIR};
 
     #[test]
     fn example1() {
@@ -54,7 +109,7 @@ mod answers {
 
     #[test]
     fn puzzle1() {
-        let mut crabs: Vec<u32> = include_str!("./input/day7")
+        let mut crabs: Vec<u32> = Inputs::new(DEFAULT_INPUT_DIR).load(7, Kind::Puzzle).unwrap()
             .split_terminator(',')
             .map(|x| x.trim().parse().unwrap())
             .collect();
@@ -64,7 +119,7 @@ mod answers {
 
     #[test]
     fn puzzle2() {
-        let mut crabs: Vec<u32> = include_str!("./input/day7")
+        let mut crabs: Vec<u32> = Inputs::new(DEFAULT_INPUT_DIR).load(7, Kind::Puzzle).unwrap()
             .split_terminator(',')
             .map(|x| x.trim().parse().unwrap())
             .collect();
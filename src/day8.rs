@@ -1,20 +1,39 @@
 use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use crate::error::column_of;
+use crate::{Error, Result, Solution};
 
+#[derive(Clone, Debug)]
 pub struct Display {
     digit_table: HashMap<String, usize>,
     outputs: [String; 4],
 }
 
 impl Display {
-    pub fn parse_input(input: &str) -> Self {
-        let (left, right) = input.split_once('|').unwrap();
+    pub fn parse_input(input: &str) -> Result<Self> {
+        let (left, right) = input
+            .split_once('|')
+            .ok_or_else(|| Error::parse("Expected a `|` between the patterns and the outputs"))?;
+
+        let patterns: Vec<&str> = left.split_whitespace().collect();
+        let output_values: Vec<&str> = right.split_whitespace().collect();
+        if patterns.len() != 10 || output_values.len() != 4 {
+            return Err(Error::parse(format!(
+                "Expected 10 patterns and 4 outputs, found {} and {}", patterns.len(), output_values.len())));
+        }
+
+        for &val in patterns.iter().chain(output_values.iter()) {
+            if !Self::is_segments(val) {
+                return Err(Error::parse(format!("Not a set of segments: {}", val)).at_column(column_of(input, val)));
+            }
+        }
 
         let mut solved: [String; 10] = Default::default();
         let mut outputs: [String; 4] = Default::default();
         let mut unsolved_6 = vec![];
         let mut unsolved_5 = vec![];
 
-        for (_, val) in left.split_whitespace().enumerate().take(10) {
+        for val in patterns {
             let val = Self::sorted(val);
             match val.len() {
                 2 => solved[1] = val,
@@ -23,10 +42,15 @@ impl Display {
                 5 => { unsolved_5.push(val); }
                 6 => { unsolved_6.push(val); }
                 7 => solved[8] = val,
-                _ => unreachable!(),
+                n => return Err(Error::parse(format!("No digit is lit with {} segments", n))),
             }
         }
 
+        let unique = [&solved[1], &solved[4], &solved[7], &solved[8]];
+        if unique.iter().any(|x| x.is_empty()) || unsolved_5.len() != 3 || unsolved_6.len() != 3 {
+            return Err(Error::parse("The patterns don't cover each of the ten digits"));
+        }
+
         // The unsolved digits fall into two groups - those with five
         // segments, and those with six. We can use the four unique ones
         // above and some simple rules to deduce which is which.
@@ -52,17 +76,30 @@ impl Display {
             }
         }
 
-        for (idx, val) in right.split_whitespace().enumerate().take(4) {
+        for (idx, val) in output_values.into_iter().enumerate() {
             outputs[idx] = val.chars().collect();
         }
 
         let mut digit_table = HashMap::new();
         for (idx, val) in solved.iter().enumerate() {
-            println!("{}: {}", idx, val);
             digit_table.insert(val.to_owned(), idx);
         }
 
-        Self { digit_table, outputs, }
+        if digit_table.len() != solved.len() || solved.iter().any(|x| x.is_empty()) {
+            return Err(Error::parse("The patterns can't be deduced into ten distinct digits"));
+        }
+
+        if let Some(bad) = outputs.iter().find(|x| !digit_table.contains_key(&Self::sorted(x))) {
+            return Err(Error::parse(format!("Output {} isn't one of the patterns", bad)));
+        }
+
+        Ok(Self { digit_table, outputs, })
+    }
+
+    /// Whether a pattern lights each of the segments `a` through `g` at most once
+    fn is_segments(input: &str) -> bool {
+        let segments: HashSet<char> = input.chars().collect();
+        segments.len() == input.len() && segments.iter().all(|x| ('a'..='g').contains(x))
     }
 
     pub fn sorted(input: &str) -> String {
@@ -95,28 +132,82 @@ impl Display {
     }
 }
 
-pub fn load_displays(input: &str) -> Vec<Display> {
-    input.lines().map(Display::parse_input).collect()
+impl FromStr for Display {
+    type Err = Error;
+
+    /// Parses a display from its ten unique signal patterns, followed by
+    /// a `|` and the four output values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aoc2021::day8::Display;
+    /// let display: Display = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf".parse()?;
+    /// assert_eq!(display.translate(), 5353);
+    ///
+    /// assert!("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab".parse::<Display>().is_err());
+    /// assert!("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb | cdfeb fcadb cdfeb cdbaf".parse::<Display>().is_err());
+    /// assert!("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ax | cdfeb fcadb cdfeb cdbaf".parse::<Display>().is_err());
+    /// assert!("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdb".parse::<Display>().is_err());
+    /// # Ok::<(), aoc2021::Error>(())
+    /// ```
+    fn from_str(input: &str) -> Result<Self> {
+        Self::parse_input(input)
+    }
+}
+
+/// Parse one display per line, skipping blank lines
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::day8::load_displays;
+/// let input = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf\n\n";
+/// assert_eq!(load_displays(input)?.len(), 1);
+///
+/// let error = load_displays("\nacedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab").unwrap_err();
+/// assert_eq!(error.to_string(), "line 2: Expected a `|` between the patterns and the outputs");
+/// # Ok::<(), aoc2021::Error>(())
+/// ```
+pub fn load_displays(input: &str) -> Result<Vec<Display>> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, x)| !x.trim().is_empty())
+        .map(|(n, line)| Display::parse_input(line).map_err(|e| e.at_line(n + 1)))
+        .collect()
+}
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Input = Vec<Display>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        load_displays(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<u64> {
+        Ok(input.iter().map(Display::count_unique_digits).sum::<usize>() as u64)
+    }
+
+    fn part2(input: &Self::Input) -> Result<u64> {
+        Ok(input.iter().map(Display::translate).sum::<usize>() as u64)
+    }
 }
 
 #[cfg(test)]
 mod answers {
     use super::*;
+    use crate::inputs::{Inputs, Kind, DEFAULT_INPUT_DIR};
 
-    static SAMPLE: &str = r#"be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
-edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
-fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
-fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
-aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
-fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
-dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
-bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
-egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
-gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce"#;
+    fn sample() -> String {
+        Inputs::new(DEFAULT_INPUT_DIR).load(8, Kind::Example).unwrap()
+    }
 
     #[test]
     fn example1() {
-        let displays = load_displays(SAMPLE);
+        let displays = load_displays(&sample()).unwrap();
         let result = displays.iter().fold(0, |acc, x| acc + x.count_unique_digits());
 
         assert_eq!(result, 26);
@@ -124,7 +215,7 @@ gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
 
     #[test]
     fn puzzle1() {
-        let displays = load_displays(include_str!("./input/day8"));
+        let displays = load_displays(&Inputs::new(DEFAULT_INPUT_DIR).load(8, Kind::Puzzle).unwrap()).unwrap();
         let result = displays.iter().fold(0, |acc, x| acc + x.count_unique_digits());
 
         assert_eq!(result, 278);
@@ -132,7 +223,7 @@ gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
 
     #[test]
     fn example2() {
-        let displays = load_displays(SAMPLE);
+        let displays = load_displays(&sample()).unwrap();
         let result = displays.iter().fold(0, |acc, x| acc + x.translate());
 
         assert_eq!(result, 61229);
@@ -140,7 +231,7 @@ gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
 
     #[test]
     fn puzzle2() {
-        let displays = load_displays(include_str!("./input/day8"));
+        let displays = load_displays(&Inputs::new(DEFAULT_INPUT_DIR).load(8, Kind::Puzzle).unwrap()).unwrap();
         let result = displays.iter().fold(0, |acc, x| acc + x.translate());
 
         assert_eq!(result, 986179);
@@ -0,0 +1,132 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use crate::inputs::Kind;
+
+/// Everything that can go wrong while loading, parsing or solving a puzzle
+#[derive(Debug)]
+pub enum Error {
+    /// The puzzle input was malformed. Positions are 1-based, and are
+    /// filled in as the error makes its way back out through the parsers.
+    Parse {
+        line: Option<usize>,
+        column: Option<usize>,
+        message: String,
+    },
+    /// None of the candidate files for a day's input exist
+    MissingInput {
+        day: u32,
+        kind: Kind,
+        tried: Vec<PathBuf>,
+    },
+    /// Input couldn't be read, from the given file if there was one
+    Io {
+        path: Option<PathBuf>,
+        source: io::Error,
+    },
+    /// The input parsed fine, but there's no answer to be had from it
+    NoAnswer(String),
+    /// A calculation went out of the range allowed for it
+    Overflow(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// A parse error with no position information yet
+    pub fn parse(message: impl Into<String>) -> Self {
+        Self::Parse { line: None, column: None, message: message.into() }
+    }
+
+    /// Attach a line number to a parse error, unless it already has one
+    pub fn at_line(self, n: usize) -> Self {
+        match self {
+            Self::Parse { line: None, column, message } => Self::Parse { line: Some(n), column, message },
+            other => other,
+        }
+    }
+
+    /// Attach a column number to a parse error, unless it already has one
+    pub fn at_column(self, n: usize) -> Self {
+        match self {
+            Self::Parse { line, column: None, message } => Self::Parse { line, column: Some(n), message },
+            other => other,
+        }
+    }
+
+    /// Shift the line number of a parse error, for parsers that work on
+    /// a block of lines somewhere in the middle of a larger input
+    pub fn offset_lines(self, by: usize) -> Self {
+        match self {
+            Self::Parse { line: Some(n), column, message } => Self::Parse { line: Some(n + by), column, message },
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Parse { line: Some(line), column: Some(column), message } => {
+                write!(f, "line {}, column {}: {}", line, column, message)
+            }
+            Self::Parse { line: Some(line), column: None, message } => write!(f, "line {}: {}", line, message),
+            Self::Parse { line: None, column: Some(column), message } => write!(f, "column {}: {}", column, message),
+            Self::Parse { line: None, column: None, message } => write!(f, "{}", message),
+            Self::MissingInput { day, kind, tried } => {
+                let tried: Vec<String> = tried.iter().map(|x| x.display().to_string()).collect();
+                write!(f, "No {} input for day {} (tried {})", kind, day, tried.join(", "))
+            }
+            Self::Io { path: Some(path), source } => write!(f, "Couldn't read {}: {}", path.display(), source),
+            Self::Io { path: None, source } => write!(f, "Couldn't read input: {}", source),
+            Self::NoAnswer(why) => write!(f, "No answer: {}", why),
+            Self::Overflow(why) => write!(f, "Overflow: {}", why),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(source: io::Error) -> Self {
+        Self::Io { path: None, source }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// The 1-based column at which `token` starts within `line`. The token
+/// must be a slice of the line, as handed out by `split_whitespace` and
+/// friends.
+pub(crate) fn column_of(line: &str, token: &str) -> usize {
+    token.as_ptr() as usize - line.as_ptr() as usize + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions() {
+        assert_eq!(Error::parse("bad").to_string(), "bad");
+        assert_eq!(Error::parse("bad").at_column(4).to_string(), "column 4: bad");
+        assert_eq!(Error::parse("bad").at_column(4).at_line(2).to_string(), "line 2, column 4: bad");
+
+        // The innermost position wins
+        assert_eq!(Error::parse("bad").at_line(2).at_line(7).to_string(), "line 2: bad");
+        assert_eq!(Error::parse("bad").at_line(2).offset_lines(10).to_string(), "line 12: bad");
+    }
+
+    #[test]
+    fn columns() {
+        let line = "forward  12";
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        assert_eq!(column_of(line, tokens[0]), 1);
+        assert_eq!(column_of(line, tokens[1]), 10);
+    }
+}
@@ -0,0 +1,390 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+use crate::error::column_of;
+use crate::{Error, Result};
+
+/// A location in a grid
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Coords {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Coords {
+    pub fn new(row: usize, col: usize) -> Self {
+        Self { row, col }
+    }
+
+    /// Step by a signed offset, if that doesn't fall off the top or left
+    /// edge of the world
+    pub fn offset(self, rows: isize, cols: isize) -> Option<Self> {
+        Some(Self {
+            row: offset(self.row, rows)?,
+            col: offset(self.col, cols)?,
+        })
+    }
+}
+
+fn offset(base: usize, by: isize) -> Option<usize> {
+    if by < 0 {
+        base.checked_sub(by.unsigned_abs())
+    } else {
+        base.checked_add(by as usize)
+    }
+}
+
+/// Which cells count as neighbors of a cell
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Only the cells sharing an edge
+    Four,
+    /// The cells sharing an edge or a corner
+    Eight,
+}
+
+const FOUR: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+const EIGHT: [(isize, isize); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+
+/// A dense, rectangular grid of cells stored in row-major order
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Default for Grid<T> {
+    fn default() -> Self {
+        Self { width: 0, height: 0, cells: vec![] }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Make a grid with every cell holding the same value. Fails if there
+    /// isn't the memory for that many cells.
+    pub fn new(width: usize, height: usize, fill: T) -> Result<Self> where T: Clone {
+        let mut cells = allocate(width, height)?;
+        cells.resize(width * height, fill);
+
+        Ok(Self { width, height, cells })
+    }
+
+    /// Make a grid from a set of rows, which must all be the same length
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aoc2021::grid::{Coords, Grid};
+    /// let grid = Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]])?;
+    /// assert_eq!((grid.width(), grid.height()), (3, 2));
+    /// assert_eq!(grid[Coords::new(1, 0)], 4);
+    ///
+    /// assert!(Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5]]).is_err());
+    /// # Ok::<(), aoc2021::Error>(())
+    /// ```
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self> {
+        let width = rows.first().map_or(0, Vec::len);
+        let height = rows.len();
+        let mut cells = Vec::with_capacity(width * height);
+
+        for (n, row) in rows.into_iter().enumerate() {
+            if row.len() != width {
+                return Err(Error::parse(format!("Expected {} cells in the row, found {}", width, row.len())).at_line(n + 1));
+            }
+
+            cells.extend(row);
+        }
+
+        Ok(Self { width, height, cells })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether a location lies within the grid
+    pub fn contains(&self, at: Coords) -> bool {
+        at.row < self.height && at.col < self.width
+    }
+
+    pub fn get(&self, at: Coords) -> Option<&T> {
+        if self.contains(at) { self.cells.get(at.row * self.width + at.col) } else { None }
+    }
+
+    pub fn get_mut(&mut self, at: Coords) -> Option<&mut T> {
+        if self.contains(at) { self.cells.get_mut(at.row * self.width + at.col) } else { None }
+    }
+
+    /// The cells of a single row, left to right
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    /// The cells of a single column, top to bottom
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        assert!(col < self.width || self.height == 0, "Column {} is outside a grid {} wide", col, self.width);
+        self.cells.iter().skip(col).step_by(self.width.max(1))
+    }
+
+    /// Every row in the grid, top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |row| self.row(row))
+    }
+
+    /// Every cell along with its location, in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = (Coords, &T)> {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(idx, val)| (Coords::new(idx / width, idx % width), val))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// The location of the first cell, in row-major order, that matches
+    pub fn position(&self, predicate: impl Fn(&T) -> bool) -> Option<Coords> {
+        self.cells
+            .iter()
+            .position(predicate)
+            .map(|idx| Coords::new(idx / self.width, idx % self.width))
+    }
+
+    /// The locations of the neighbors of a cell that lie within the grid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aoc2021::grid::{Connectivity, Coords, Grid};
+    /// let grid = Grid::new(3, 3, 0)?;
+    /// assert_eq!(grid.neighbors(Coords::new(0, 0), Connectivity::Four).count(), 2);
+    /// assert_eq!(grid.neighbors(Coords::new(0, 1), Connectivity::Eight).count(), 5);
+    /// assert_eq!(grid.neighbors(Coords::new(1, 1), Connectivity::Eight).count(), 8);
+    /// # Ok::<(), aoc2021::Error>(())
+    /// ```
+    pub fn neighbors(&self, at: Coords, connectivity: Connectivity) -> impl Iterator<Item = Coords> + '_ {
+        let offsets: &[(isize, isize)] = match connectivity {
+            Connectivity::Four => &FOUR,
+            Connectivity::Eight => &EIGHT,
+        };
+
+        offsets
+            .iter()
+            .filter_map(move |&(rows, cols)| at.offset(rows, cols))
+            .filter(move |&x| self.contains(x))
+    }
+
+    /// Grow or shrink the grid, keeping the cells that are still in bounds
+    /// where they were, and filling any new ones. Fails, leaving the grid
+    /// as it was, if there isn't the memory for the new size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aoc2021::grid::{Coords, Grid};
+    /// let mut grid = Grid::from_rows(vec![vec![1, 2], vec![3, 4]])?;
+    /// grid.resize(3, 3, 0)?;
+    /// assert_eq!(grid.row(1), &[3, 4, 0]);
+    /// assert_eq!(grid.row(2), &[0, 0, 0]);
+    ///
+    /// grid.resize(1, 2, 0)?;
+    /// assert_eq!(grid.column(0).collect::<Vec<_>>(), vec![&1, &3]);
+    ///
+    /// assert!(grid.resize(usize::MAX, 2, 0).is_err());
+    /// assert_eq!(grid.height(), 2);
+    /// # Ok::<(), aoc2021::Error>(())
+    /// ```
+    pub fn resize(&mut self, width: usize, height: usize, fill: T) -> Result<()> where T: Clone {
+        let mut cells = allocate(width, height)?;
+        let mut old = std::mem::take(&mut self.cells).into_iter();
+
+        for row in 0..height {
+            let mut kept = 0;
+            if row < self.height {
+                kept = self.width.min(width);
+                cells.extend(old.by_ref().take(kept));
+                old.by_ref().take(self.width - kept).for_each(drop);
+            }
+
+            cells.extend(std::iter::repeat(fill.clone()).take(width - kept));
+        }
+
+        *self = Self { width, height, cells };
+        Ok(())
+    }
+
+    /// Make a new grid of the same shape by transforming every cell
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid { width: self.width, height: self.height, cells: self.cells.iter().map(f).collect() }
+    }
+}
+
+/// Room for the cells of a grid of the given size, if it can be had
+fn allocate<T>(width: usize, height: usize) -> Result<Vec<T>> {
+    let too_big = || Error::Overflow(format!("There isn't room for a {}x{} grid", width, height));
+    let mut cells = vec![];
+    cells
+        .try_reserve_exact(width.checked_mul(height).ok_or_else(too_big)?)
+        .map_err(|_| too_big())?;
+
+    Ok(cells)
+}
+
+impl<T: FromStr> Grid<T> {
+    /// Parse a block of text where each line is a row, and cells are
+    /// separated by whitespace
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aoc2021::grid::{Coords, Grid};
+    /// let grid: Grid<u32> = Grid::parse_whitespace("22 13 17\n 8  2 23")?;
+    /// assert_eq!(grid[Coords::new(1, 2)], 23);
+    ///
+    /// let error = Grid::<u32>::parse_whitespace("22 13 17\n 8  x 23").unwrap_err();
+    /// assert_eq!(error.to_string(), "line 2, column 5: Bad cell: x");
+    /// # Ok::<(), aoc2021::Error>(())
+    /// ```
+    pub fn parse_whitespace(text: &str) -> Result<Self> {
+        let rows = text
+            .lines()
+            .enumerate()
+            .map(|(n, line)| {
+                line.split_whitespace()
+                    .map(|val| {
+                        val.parse()
+                            .map_err(|_| Error::parse(format!("Bad cell: {}", val)).at_column(column_of(line, val)).at_line(n + 1))
+                    })
+                    .collect::<Result<Vec<T>>>()
+            })
+            .collect::<Result<_>>()?;
+
+        Self::from_rows(rows)
+    }
+}
+
+impl<T> Grid<T> {
+    /// Parse a block of text where each line is a row, and each character
+    /// is a cell
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aoc2021::grid::{Coords, Grid};
+    /// let grid = Grid::parse_chars("123\n456", |c| c.to_digit(10))?;
+    /// assert_eq!(grid[Coords::new(1, 1)], 5);
+    ///
+    /// let error = Grid::parse_chars("123\n4x6", |c| c.to_digit(10)).unwrap_err();
+    /// assert_eq!(error.to_string(), "line 2, column 2: Bad cell: x");
+    /// # Ok::<(), aoc2021::Error>(())
+    /// ```
+    pub fn parse_chars(text: &str, cell: impl Fn(char) -> Option<T>) -> Result<Self> {
+        let rows = text
+            .lines()
+            .enumerate()
+            .map(|(n, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(|(idx, c)| cell(c).ok_or_else(|| Error::parse(format!("Bad cell: {}", c)).at_column(idx + 1).at_line(n + 1)))
+                    .collect::<Result<Vec<T>>>()
+            })
+            .collect::<Result<_>>()?;
+
+        Self::from_rows(rows)
+    }
+}
+
+impl<T> Index<Coords> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, at: Coords) -> &T {
+        let (width, height) = (self.width, self.height);
+        self.get(at)
+            .unwrap_or_else(|| panic!("{:?} is outside a {}x{} grid", at, width, height))
+    }
+}
+
+impl<T> IndexMut<Coords> for Grid<T> {
+    fn index_mut(&mut self, at: Coords) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(at)
+            .unwrap_or_else(|| panic!("{:?} is outside a {}x{} grid", at, width, height))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    /// Lays the grid out as rows of right-aligned, space separated cells
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aoc2021::grid::Grid;
+    /// let grid = Grid::from_rows(vec![vec![1, 22], vec![333, 4]])?;
+    /// assert_eq!(grid.to_string(), "  1  22\n333   4\n");
+    /// # Ok::<(), aoc2021::Error>(())
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cells: Vec<String> = self.cells.iter().map(ToString::to_string).collect();
+        let pad = cells.iter().map(String::len).max().unwrap_or(0);
+
+        for row in cells.chunks(self.width.max(1)) {
+            let row: Vec<String> = row.iter().map(|x| format!("{:>1$}", x, pad)).collect();
+            writeln!(f, "{}", row.join(" "))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+
+        assert_eq!(grid.rows().collect::<Vec<_>>(), vec![&[1, 2, 3], &[4, 5, 6]]);
+        assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), vec![2, 5]);
+        assert_eq!(grid.position(|&x| x == 6), Some(Coords::new(1, 2)));
+        assert_eq!(grid.iter().nth(4), Some((Coords::new(1, 1), &5)));
+    }
+
+    #[test]
+    fn bounds() {
+        let mut grid = Grid::new(2, 3, 'x').unwrap();
+        assert!(grid.contains(Coords::new(2, 1)));
+        assert!(!grid.contains(Coords::new(1, 2)));
+        assert_eq!(grid.get(Coords::new(3, 0)), None);
+
+        grid[Coords::new(2, 1)] = 'y';
+        assert_eq!(grid.get(Coords::new(2, 1)), Some(&'y'));
+
+        // Too big to allocate is an error rather than an abort
+        assert!(Grid::new(1 << 40, 1 << 20, 0_u8).is_err());
+        assert!(Grid::new(usize::MAX, 2, 0_u8).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "outside a 2x3 grid")]
+    fn out_of_bounds() {
+        let grid = Grid::new(2, 3, 0).unwrap();
+        let _ = grid[Coords::new(0, 2)];
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = Grid::new(3, 2, 0).unwrap();
+        let mut around: Vec<Coords> = grid.neighbors(Coords::new(1, 2), Connectivity::Eight).collect();
+        around.sort();
+
+        assert_eq!(around, vec![Coords::new(0, 1), Coords::new(0, 2), Coords::new(1, 1)]);
+    }
+}
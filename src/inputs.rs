@@ -0,0 +1,171 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::{Error, Result};
+
+/// Environment variable naming the directory puzzle inputs are read from
+pub const INPUT_DIR_VAR: &str = "AOC2021_INPUT_DIR";
+
+/// Config file, looked for in the working directory, that can name the
+/// input directory with an `input_dir = <path>` line
+pub const CONFIG_FILE: &str = "aoc2021.conf";
+
+/// The inputs that ship alongside the source, used when nothing else
+/// has been configured
+pub const DEFAULT_INPUT_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/input");
+
+/// Which flavor of input to load for a day
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// The full puzzle input
+    Puzzle,
+    /// The worked example from the puzzle text
+    Example,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Puzzle => f.pad("puzzle"),
+            Self::Example => f.pad("example"),
+        }
+    }
+}
+
+/// Resolves puzzle inputs from a directory at runtime
+#[derive(Clone, Debug)]
+pub struct Inputs {
+    dir: PathBuf,
+}
+
+impl Inputs {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Locate the input directory, preferring the `AOC2021_INPUT_DIR`
+    /// environment variable, then an `aoc2021.conf` in the working
+    /// directory, and finally the inputs bundled with the crate.
+    pub fn from_env() -> Result<Self> {
+        if let Some(dir) = env::var_os(INPUT_DIR_VAR) {
+            return Ok(Self::new(dir));
+        }
+
+        let config = Path::new(CONFIG_FILE);
+        if config.is_file() {
+            return Self::from_config(config);
+        }
+
+        Ok(Self::new(DEFAULT_INPUT_DIR))
+    }
+
+    /// Read the input directory from a config file. Blank lines and lines
+    /// starting with `#` are ignored, and a relative `input_dir` is taken
+    /// relative to the config file itself.
+    pub fn from_config(path: &Path) -> Result<Self> {
+        let text = read(path)?;
+
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| Error::parse(format!("Expected `key = value` in {}", path.display())).at_line(n + 1))?;
+
+            match key.trim() {
+                "input_dir" => {
+                    let base = path.parent().unwrap_or_else(|| Path::new(""));
+                    return Ok(Self::new(base.join(value.trim().trim_matches('"'))));
+                }
+                unknown => return Err(Error::parse(format!("Unknown key `{}` in {}", unknown, path.display())).at_line(n + 1)),
+            }
+        }
+
+        Err(Error::parse(format!("{} doesn't set an input_dir", path.display())))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The file names that are tried, in order, for a given day's input
+    pub fn candidates(&self, day: u32, kind: Kind) -> Vec<PathBuf> {
+        let stem = match kind {
+            Kind::Puzzle => format!("day{}", day),
+            Kind::Example => format!("day{}.example", day),
+        };
+
+        vec![self.dir.join(&stem), self.dir.join(format!("{}.txt", stem))]
+    }
+
+    /// Find the file holding a given day's input
+    pub fn path(&self, day: u32, kind: Kind) -> Result<PathBuf> {
+        let candidates = self.candidates(day, kind);
+        match candidates.iter().find(|x| x.is_file()) {
+            Some(path) => Ok(path.clone()),
+            None => Err(Error::MissingInput { day, kind, tried: candidates }),
+        }
+    }
+
+    /// Read a given day's input
+    pub fn load(&self, day: u32, kind: Kind) -> Result<String> {
+        read(&self.path(day, kind)?)
+    }
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|source| Error::Io { path: Some(path.to_owned()), source })
+}
+
+/// Load the puzzle input for a day from the configured input directory
+pub fn load(day: u32) -> Result<String> {
+    Inputs::from_env()?.load(day, Kind::Puzzle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("aoc2021-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn bundled_inputs() {
+        let inputs = Inputs::new(DEFAULT_INPUT_DIR);
+        assert!(inputs.load(1, Kind::Puzzle).unwrap().starts_with("173"));
+    }
+
+    #[test]
+    fn txt_and_example_files() {
+        let dir = scratch_dir("inputs");
+        fs::write(dir.join("day3.txt"), "puzzle").unwrap();
+        fs::write(dir.join("day3.example"), "example").unwrap();
+
+        let inputs = Inputs::new(&dir);
+        assert_eq!(inputs.load(3, Kind::Puzzle).unwrap(), "puzzle");
+        assert_eq!(inputs.load(3, Kind::Example).unwrap(), "example");
+
+        let missing = inputs.load(4, Kind::Puzzle).unwrap_err().to_string();
+        assert!(missing.contains("day 4"), "{}", missing);
+        assert!(missing.contains("day4.txt"), "{}", missing);
+    }
+
+    #[test]
+    fn config_file() {
+        let dir = scratch_dir("config");
+        let config = dir.join(CONFIG_FILE);
+
+        fs::write(&config, "# where the inputs live\ninput_dir = \"puzzles\"\n").unwrap();
+        assert_eq!(Inputs::from_config(&config).unwrap().dir(), dir.join("puzzles"));
+
+        fs::write(&config, "inputs = puzzles\n").unwrap();
+        assert!(Inputs::from_config(&config).is_err());
+    }
+}
@@ -1,4 +1,9 @@
-#![feature(iter_partition_in_place)]
+#![cfg_attr(feature = "nightly", feature(iter_partition_in_place))]
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 pub mod day1;
 pub mod day2;
@@ -9,3 +14,155 @@ pub mod day6;
 pub mod day7;
 
 pub mod day8;
+
+pub mod answers;
+pub mod bench;
+pub mod error;
+pub mod grid;
+pub mod inputs;
+pub mod report;
+
+pub use error::{Error, Result};
+
+/// Which half of a day's puzzle to solve
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl FromStr for Part {
+    type Err = Error;
+
+    /// Parses a part from its number, as it would be given on a
+    /// command line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aoc2021::Part;
+    /// assert_eq!("1".parse::<Part>()?, Part::One);
+    /// assert_eq!("2".parse::<Part>()?, Part::Two);
+    /// assert!("3".parse::<Part>().is_err());
+    /// # Ok::<(), aoc2021::Error>(())
+    /// ```
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "1" => Ok(Self::One),
+            "2" => Ok(Self::Two),
+            unknown => Err(Error::parse(format!("Puzzles only have parts 1 and 2, not {}", unknown))),
+        }
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::One => f.pad("1"),
+            Self::Two => f.pad("2"),
+        }
+    }
+}
+
+/// The common shape of every day's puzzle. The raw puzzle input is
+/// parsed once, and then both parts are solved against the parsed form.
+pub trait Solution {
+    type Input;
+
+    /// Parse the raw puzzle text into whatever form the solvers want
+    fn parse(input: &str) -> Result<Self::Input>;
+
+    /// Solve the first half of the puzzle
+    fn part1(input: &Self::Input) -> Result<u64>;
+
+    /// Solve the second half of the puzzle
+    fn part2(input: &Self::Input) -> Result<u64>;
+}
+
+/// A type-erased `Solution`, so that every day can be driven through
+/// the same interface regardless of what its parsed input looks like.
+pub trait Solver: Sync {
+    /// The day of the advent calendar this solver is for
+    fn day(&self) -> u32;
+
+    /// Parse the raw input and solve the requested part, keeping track
+    /// of how long each phase took
+    fn run(&self, input: &str, part: Part) -> Result<Run>;
+
+    /// Parse the raw input and solve the requested part
+    fn solve(&self, input: &str, part: Part) -> Result<u64> {
+        self.run(input, part).map(|x| x.answer)
+    }
+}
+
+/// The answer to one part of a puzzle, and how long it took to get there
+#[derive(Clone, Copy, Debug)]
+pub struct Run {
+    pub answer: u64,
+    pub parse_time: Duration,
+    pub solve_time: Duration,
+}
+
+struct Registered<S> {
+    day: u32,
+    solution: PhantomData<fn() -> S>,
+}
+
+impl<S: Solution> Solver for Registered<S> {
+    fn day(&self) -> u32 {
+        self.day
+    }
+
+    fn run(&self, input: &str, part: Part) -> Result<Run> {
+        let start = Instant::now();
+        let parsed = S::parse(input)?;
+        let parse_time = start.elapsed();
+
+        let start = Instant::now();
+        let answer = match part {
+            Part::One => S::part1(&parsed),
+            Part::Two => S::part2(&parsed),
+        }?;
+        let solve_time = start.elapsed();
+
+        Ok(Run { answer, parse_time, solve_time })
+    }
+}
+
+const fn register<S: Solution>(day: u32) -> Registered<S> {
+    Registered { day, solution: PhantomData }
+}
+
+static DAY1: Registered<day1::Day1> = register(1);
+static DAY2: Registered<day2::Day2> = register(2);
+static DAY3: Registered<day3::Day3> = register(3);
+static DAY4: Registered<day4::Day4> = register(4);
+static DAY5: Registered<day5::Day5> = register(5);
+static DAY6: Registered<day6::Day6> = register(6);
+static DAY7: Registered<day7::Day7> = register(7);
+static DAY8: Registered<day8::Day8> = register(8);
+
+static REGISTRY: [&dyn Solver; 8] = [&DAY1, &DAY2, &DAY3, &DAY4, &DAY5, &DAY6, &DAY7, &DAY8];
+
+/// Every solver that has been implemented so far, in calendar order
+pub fn solvers() -> &'static [&'static dyn Solver] {
+    &REGISTRY
+}
+
+/// Look up the solver for a given day, if there is one
+///
+/// # Examples
+///
+/// ```
+/// use aoc2021::{solver, Part};
+/// let day1 = solver(1).unwrap();
+/// let sample = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263";
+/// assert_eq!(day1.solve(sample, Part::One)?, 7);
+/// assert_eq!(day1.solve(sample, Part::Two)?, 5);
+///
+/// assert!(solver(25).is_none());
+/// # Ok::<(), aoc2021::Error>(())
+/// ```
+pub fn solver(day: u32) -> Option<&'static dyn Solver> {
+    solvers().iter().copied().find(|s| s.day() == day)
+}
@@ -0,0 +1,109 @@
+use std::fmt::Write;
+use crate::inputs::{Inputs, Kind};
+use crate::{Part, Run, Solver};
+
+/// The outcome of running one part of a day's puzzle
+#[derive(Debug)]
+pub struct Record {
+    pub day: u32,
+    pub part: Part,
+    pub outcome: Result<Run, String>,
+}
+
+impl Record {
+    /// Render the record as a single line of JSON. Times are given in
+    /// nanoseconds, and whichever of the answer or the error doesn't
+    /// apply is `null`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use aoc2021::{Part, Run};
+    /// use aoc2021::report::Record;
+    ///
+    /// let run = Run { answer: 7, parse_time: Duration::from_nanos(10), solve_time: Duration::from_nanos(2) };
+    /// let ok = Record { day: 1, part: Part::One, outcome: Ok(run) };
+    /// assert_eq!(ok.to_json(), r#"{"day":1,"part":1,"answer":7,"parse_ns":10,"solve_ns":2,"error":null}"#);
+    ///
+    /// let failed = Record { day: 4, part: Part::Two, outcome: Err("line 3: \"x\" isn't a number".into()) };
+    /// assert_eq!(failed.to_json(),
+    ///            r#"{"day":4,"part":2,"answer":null,"parse_ns":null,"solve_ns":null,"error":"line 3: \"x\" isn't a number"}"#);
+    /// ```
+    pub fn to_json(&self) -> String {
+        let (answer, parse, solve, error) = match &self.outcome {
+            Ok(run) => (
+                run.answer.to_string(),
+                run.parse_time.as_nanos().to_string(),
+                run.solve_time.as_nanos().to_string(),
+                "null".to_string(),
+            ),
+            Err(e) => ("null".into(), "null".into(), "null".into(), json_string(e)),
+        };
+
+        format!(
+            r#"{{"day":{},"part":{},"answer":{},"parse_ns":{},"solve_ns":{},"error":{}}}"#,
+            self.day, self.part, answer, parse, solve, error)
+    }
+}
+
+/// Quote and escape a string for inclusion in JSON
+pub fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => { let _ = write!(out, "\\u{:04x}", c as u32); }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Run both parts of each of the given solvers against their inputs,
+/// collecting what happened rather than stopping at the first failure
+pub fn run_all(solvers: &[&dyn Solver], inputs: &Inputs, kind: Kind) -> Vec<Record> {
+    let mut records = vec![];
+
+    for solver in solvers {
+        let day = solver.day();
+        let input = inputs.load(day, kind).map_err(|e| e.to_string());
+
+        for part in [Part::One, Part::Two] {
+            let outcome = match &input {
+                Ok(input) => solver.run(input, part).map_err(|e| e.to_string()),
+                Err(e) => Err(e.clone()),
+            };
+
+            records.push(Record { day, part, outcome });
+        }
+    }
+
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escaping() {
+        assert_eq!(json_string("plain"), r#""plain""#);
+        assert_eq!(json_string("a\\b\n\t\u{1}"), r#""a\\b\n\t\u0001""#);
+    }
+
+    #[test]
+    fn missing_inputs() {
+        let inputs = Inputs::new(std::env::temp_dir().join("aoc2021-nowhere"));
+        let records = run_all(&[crate::solver(2).unwrap()], &inputs, Kind::Puzzle);
+
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().all(|x| x.outcome.as_ref().unwrap_err().contains("No puzzle input for day 2")));
+    }
+}
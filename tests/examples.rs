@@ -0,0 +1,50 @@
+//! Runs the worked example from each day's puzzle text (`dayN.example` in
+//! the bundled inputs) through the same parse and solve path as the real
+//! puzzle inputs, and checks the answers against `examples.toml`.
+
+use aoc2021::answers::{Answers, Verdict};
+use aoc2021::inputs::{Inputs, Kind, DEFAULT_INPUT_DIR};
+use aoc2021::report::run_all;
+
+fn check_example(day: u32) {
+    let inputs = Inputs::new(DEFAULT_INPUT_DIR);
+    let answers = Answers::load(&inputs, Kind::Example).unwrap();
+    let solver = aoc2021::solver(day).unwrap();
+
+    for record in run_all(&[solver], &inputs, Kind::Example) {
+        assert_eq!(answers.check(&record), Verdict::Pass, "day {} part {}", day, record.part);
+    }
+}
+
+macro_rules! examples {
+    ($($name:ident => $day:expr),* $(,)?) => {
+        $(
+            #[test]
+            fn $name() {
+                check_example($day);
+            }
+        )*
+    };
+}
+
+examples! {
+    day1 => 1,
+    day2 => 2,
+    day3 => 3,
+    day4 => 4,
+    day5 => 5,
+    day6 => 6,
+    day7 => 7,
+    day8 => 8,
+}
+
+#[test]
+fn every_day_has_an_example() {
+    let inputs = Inputs::new(DEFAULT_INPUT_DIR);
+    let answers = Answers::load(&inputs, Kind::Example).unwrap();
+
+    for solver in aoc2021::solvers() {
+        assert!(inputs.path(solver.day(), Kind::Example).is_ok(), "day {} has no example", solver.day());
+        assert!(answers.expected(solver.day(), aoc2021::Part::One).is_some(), "day {} has no example answers", solver.day());
+    }
+}